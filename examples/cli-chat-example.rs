@@ -53,12 +53,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = ChatGPTClient::new(&api_key, "https://api.openai.com");
 
     // Initialize the message history with a system message
-    let mut messages = vec![Message {
-        role: Role::System,
-        content:
-            "Be a helpfull pair programmer, who want to show solutions and examples in code blocks"
-                .to_string(),
-    }];
+    let mut messages = vec![Message::new(
+        Role::System,
+        "Be a helpfull pair programmer, who want to show solutions and examples in code blocks",
+    )];
 
     // Check if any command line arguments are provided
     let mut args: Skip<env::Args> = env::args().skip(1);
@@ -95,10 +93,7 @@ async fn process_user_input(
     user_message_content: String,
 ) -> Result<(), ChatGPTError> {
     // Add the user message to the message history
-    messages.push(Message {
-        role: Role::User,
-        content: user_message_content.trim().to_string(),
-    });
+    messages.push(Message::new(Role::User, user_message_content.trim()));
 
     // Prepare the ChatInput object for the API call
     let input = ChatInput {
@@ -125,7 +120,7 @@ async fn process_user_input(
     };
 
     // Extract the assistant's message from the API response
-    let assistant_message = &chat.choices[0].message.content;
+    let assistant_message = chat.choices[0].message.content.to_string();
 
     // Display the computer's response with an optional icon
     let computer_label: StyledObject<&str> = if env::var("USE_ICONS")
@@ -142,10 +137,7 @@ async fn process_user_input(
     println!("{}{}", computer_label, computer_response);
 
     // Add the assistant's message to the message history
-    messages.push(Message {
-        role: Role::Assistant,
-        content: assistant_message.clone(),
-    });
+    messages.push(Message::new(Role::Assistant, &assistant_message));
 
     Ok(())
 }
@@ -32,10 +32,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let client = ChatGPTClient::new(&api_key, "https://api.openai.com");
 
     // Create a vector of messages with an initial system message
-    let mut messages = vec![Message {
-        role: Role::System,
-        content: "You are an AI that can answer any question.".to_string(),
-    }];
+    let mut messages = vec![Message::new(
+        Role::System,
+        "You are an AI that can answer any question.",
+    )];
 
     // Start an input loop
     loop {
@@ -48,10 +48,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         stdin().read_line(&mut user_input).unwrap();
 
         // Add the user's message to the messages vector
-        messages.push(Message {
-            role: Role::User,
-            content: user_input.trim().to_string(),
-        });
+        messages.push(Message::new(Role::User, user_input.trim()));
 
         // Define the input for the ChatGPTClient
         let input = ChatInput {
@@ -64,15 +61,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
         let response = client.chat(input).await?;
 
         // Retrieve the AI's response from the first choice
-        let ai_message = &response.choices[0].message.content;
+        let ai_message = response.choices[0].message.content.to_string();
 
         // Print the AI's response to the console
         println!("AI Response: {}", ai_message);
 
         // Add the AI's message to the messages vector
-        messages.push(Message {
-            role: Role::Assistant,
-            content: ai_message.clone(),
-        });
+        messages.push(Message::new(Role::Assistant, &ai_message));
     }
 }
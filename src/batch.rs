@@ -0,0 +1,94 @@
+use crate::client::{ChatGPTError, ChatInput};
+use serde::Serialize;
+
+/// The endpoint the generated batch requests are sent to.
+pub const CHAT_COMPLETIONS_ENDPOINT: &str = "/v1/chat/completions";
+
+/// Represents a single line of a batch input file.
+#[derive(Debug, Serialize)]
+struct BatchRequestLine<'a> {
+    custom_id: &'a str,
+    method: &'static str,
+    url: &'static str,
+    body: &'a ChatInput,
+}
+
+/// Builds the JSONL contents of a batch input file from chat inputs.
+///
+/// Each `(custom_id, input)` pair becomes one line of the form
+/// `{"custom_id": ..., "method": "POST", "url": "/v1/chat/completions", "body": ...}`.
+/// The resulting bytes can be uploaded as a file with the `batch` purpose.
+///
+/// # Arguments
+///
+/// * `requests` - The chat inputs, each paired with a unique custom id.
+///
+/// # Errors
+///
+/// Returns a ChatGPTError::Serialization if an input cannot be serialized.
+pub fn build_batch_input(requests: &[(String, ChatInput)]) -> Result<Vec<u8>, ChatGPTError> {
+    let mut output = Vec::new();
+    for (custom_id, input) in requests {
+        let line = BatchRequestLine {
+            custom_id,
+            method: "POST",
+            url: CHAT_COMPLETIONS_ENDPOINT,
+            body: input,
+        };
+        serde_json::to_writer(&mut output, &line)?;
+        output.push(b'\n');
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Message;
+    use crate::models::{Model, Role};
+
+    fn chat_input(content: &str) -> ChatInput {
+        ChatInput {
+            model: Model::Gpt_4o,
+            messages: vec![Message::new(Role::User, content)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_batch_input() {
+        let requests = vec![
+            ("request-1".to_string(), chat_input("Hello")),
+            ("request-2".to_string(), chat_input("Goodbye")),
+        ];
+
+        let output = build_batch_input(&requests).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert!(text.ends_with('\n'));
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            serde_json::json!({
+                "custom_id": "request-1",
+                "method": "POST",
+                "url": "/v1/chat/completions",
+                "body": {
+                    "model": "gpt-4o",
+                    "messages": [{"role": "user", "content": "Hello"}]
+                }
+            })
+        );
+        assert_eq!(lines[1]["custom_id"], "request-2");
+        assert_eq!(lines[1]["body"]["messages"][0]["content"], "Goodbye");
+    }
+
+    #[test]
+    fn test_build_batch_input_empty() {
+        assert!(build_batch_input(&[]).unwrap().is_empty());
+    }
+}
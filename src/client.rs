@@ -1,18 +1,58 @@
-use crate::models::{LogitBias, Model, Role};
+use crate::models::{FinishReason, LogitBias, Model, Role, ServiceTier};
+use crate::pricing;
+use crate::rate_limit::{self, RateLimitInfo};
+use crate::templates;
+use crate::tools::{Tool, ToolCall, ToolChoice};
 use log::debug;
-use reqwest::{header::HeaderMap, Client, StatusCode};
-use serde::{Deserialize, Serialize};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client, Proxy, RequestBuilder, Response, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
+/// The default base URL for the OpenAI API.
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+
+/// The default User-Agent sent with every request.
+pub const DEFAULT_USER_AGENT: &str = concat!("chat-gpt-lib-rs/", env!("CARGO_PKG_VERSION"));
+
+/// The default base delay between retries, doubled for every attempt.
+pub const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// The default maximum delay between retries, also for a longer `Retry-After` sent by the API.
+pub const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
 /// Main ChatGPTClient struct.
 pub struct ChatGPTClient {
     base_url: String,
     api_key: String,
+    api_key_provider: Option<ApiKeyProvider>,
+    error_on_truncation: bool,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    max_retry_delay: Duration,
+    organization: Option<String>,
+    project: Option<String>,
+    default_headers: HeaderMap,
     client: Client,
 }
 
+/// A callback returning the API key to use for a request, for keys that are rotated.
+pub type ApiKeyProvider = Arc<dyn Fn() -> String + Send + Sync>;
+
 /// Represents the input for the chat API call.
-#[derive(Debug, Serialize)]
+///
+/// The input also implements `Deserialize`, so presets can be loaded from a configuration file.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ChatInput {
     pub model: Model,
     pub messages: Vec<Message>,
@@ -35,7 +75,198 @@ pub struct ChatInput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logit_bias: Option<LogitBias>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prediction: Option<Prediction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_search_options: Option<WebSearchOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ChatResponseFormat>,
+}
+
+/// Represents a predicted output, which speeds up responses when most of the output is known
+/// in advance, such as when making small edits to a text or code file.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Prediction {
+    /// The expected content of the response.
+    Content { content: String },
+}
+
+/// Builder for a [`ChatInput`], see [`ChatInput::builder`].
+#[derive(Debug)]
+pub struct ChatInputBuilder {
+    input: ChatInput,
+}
+
+impl ChatInputBuilder {
+    /// Creates a new ChatInputBuilder for the given model, without messages.
+    pub fn new(model: Model) -> Self {
+        Self {
+            input: ChatInput {
+                model,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Appends a message.
+    pub fn with_message(mut self, message: Message) -> Self {
+        self.input.messages.push(message);
+        self
+    }
+
+    /// Appends the given messages.
+    pub fn with_messages(mut self, messages: impl IntoIterator<Item = Message>) -> Self {
+        self.input.messages.extend(messages);
+        self
+    }
+
+    /// Sets the sampling temperature, between 0 and 2.
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.input.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets the nucleus sampling probability mass.
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.input.top_p = Some(top_p);
+        self
+    }
+
+    /// Sets the number of choices to generate.
+    pub fn with_n(mut self, n: usize) -> Self {
+        self.input.n = Some(n);
+        self
+    }
+
+    /// Sets the maximum number of tokens to generate.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.input.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Sets the sequences where the model stops generating.
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.input.stop = Some(stop);
+        self
+    }
+
+    /// Sets the presence penalty, between -2 and 2.
+    pub fn with_presence_penalty(mut self, presence_penalty: f64) -> Self {
+        self.input.presence_penalty = Some(presence_penalty);
+        self
+    }
+
+    /// Sets the frequency penalty, between -2 and 2.
+    pub fn with_frequency_penalty(mut self, frequency_penalty: f64) -> Self {
+        self.input.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    /// Sets the seed for mostly deterministic sampling.
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.input.seed = Some(seed);
+        self
+    }
+
+    /// Sets the identifier of the end user, to help the API detect abuse.
+    pub fn with_user(mut self, user: &str) -> Self {
+        self.input.user = Some(user.to_string());
+        self
+    }
+
+    /// Adds a metadata key-value pair, stored with the completion when `store` is enabled.
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.input
+            .metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Sets whether the completion is stored.
+    pub fn with_store(mut self, store: bool) -> Self {
+        self.input.store = Some(store);
+        self
+    }
+
+    /// Sets the format the model must output.
+    pub fn with_response_format(mut self, response_format: ChatResponseFormat) -> Self {
+        self.input.response_format = Some(response_format);
+        self
+    }
+
+    /// Sets the tools the model may call.
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.input.tools = Some(tools);
+        self
+    }
+
+    /// Sets which tool, if any, the model calls.
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.input.tool_choice = Some(tool_choice);
+        self
+    }
+
+    /// Builds the ChatInput.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if no messages were added, or if the input
+    /// does not pass [`ChatInput::validate`].
+    pub fn build(self) -> Result<ChatInput, ChatGPTError> {
+        if self.input.messages.is_empty() {
+            return Err(ChatGPTError::ConfigError(
+                "A chat input requires at least one message".to_string(),
+            ));
+        }
+        self.input.validate()?;
+        Ok(self.input)
+    }
+}
+
+/// Represents the format the model must output.
+///
+/// The `ChatResponseFormat` enum has the following variants:
+/// - `Text`: The model outputs text, which is the default.
+/// - `JsonObject`: The model outputs valid JSON. The messages must instruct the model to produce JSON.
+/// - `JsonSchema`: The model outputs JSON matching the given schema.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+/// Represents a JSON Schema the model output must match.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+    /// Enables strict schema adherence, which supports a subset of JSON Schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
 }
 
 impl Default for ChatInput {
@@ -52,8 +283,97 @@ impl Default for ChatInput {
             presence_penalty: None,
             frequency_penalty: None,
             logit_bias: None,
+            logprobs: None,
+            top_logprobs: None,
             user: None,
+            seed: None,
+            store: None,
+            metadata: None,
+            service_tier: None,
+            prediction: None,
+            web_search_options: None,
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+        }
+    }
+}
+
+impl ChatInput {
+    /// The maximum number of stop sequences accepted by the API.
+    pub const MAX_STOP_SEQUENCES: usize = 4;
+
+    /// Returns a ChatInputBuilder for constructing a ChatInput for the given model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chat_gpt_lib_rs::{ChatInput, Message, Model, Role};
+    ///
+    /// let input = ChatInput::builder(Model::Gpt_4o)
+    ///     .with_message(Message::new(Role::System, "You are a helpful assistant."))
+    ///     .with_message(Message::new(Role::User, "Who is the best field hockey player?"))
+    ///     .with_temperature(0.7)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder(model: Model) -> ChatInputBuilder {
+        ChatInputBuilder::new(model)
+    }
+
+    /// Checks the input for mistakes the API would reject, before making a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if `stream` is set to `true`, if more than
+    /// [`ChatInput::MAX_STOP_SEQUENCES`] stop sequences are given, or if a stop sequence is empty.
+    pub fn validate(&self) -> Result<(), ChatGPTError> {
+        // The response is parsed as a single JSON object, which a streamed response is not
+        if self.stream == Some(true) {
+            return Err(ChatGPTError::ConfigError(
+                "Streaming is not supported by this client, leave `stream` unset".to_string(),
+            ));
+        }
+
+        if let Some(stop) = &self.stop {
+            if stop.len() > Self::MAX_STOP_SEQUENCES {
+                return Err(ChatGPTError::ConfigError(format!(
+                    "At most {} stop sequences are allowed, got {}",
+                    Self::MAX_STOP_SEQUENCES,
+                    stop.len()
+                )));
+            }
+            if stop.iter().any(String::is_empty) {
+                return Err(ChatGPTError::ConfigError(
+                    "Stop sequences must not be empty".to_string(),
+                ));
+            }
         }
+
+        Ok(())
+    }
+
+    /// Returns the pretty-printed JSON body that would be sent to the API.
+    ///
+    /// Useful for troubleshooting rejected requests; fields that are `None` are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::Serialization if the input cannot be serialized.
+    pub fn to_json(&self) -> Result<String, ChatGPTError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the compact JSON body exactly as it is sent to the API.
+    ///
+    /// Useful for logging requests or reproducing them with curl; fields that are `None`
+    /// are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::Serialization if the input cannot be serialized.
+    pub fn to_request_body(&self) -> Result<String, ChatGPTError> {
+        Ok(serde_json::to_string(self)?)
     }
 }
 
@@ -66,28 +386,396 @@ pub struct ChatResponse {
     pub model: String,
     pub usage: Usage,
     pub choices: Vec<Choice>,
+    pub service_tier: Option<ServiceTier>,
+    pub system_fingerprint: Option<String>,
+}
+
+impl ChatResponse {
+    /// Returns the message of the first choice, or `None` if the response has no choices.
+    pub fn first_message(&self) -> Option<&Message> {
+        self.choices.first().map(|choice| &choice.message)
+    }
+
+    /// Returns true if any choice stopped because of the token limit or a content filter.
+    pub fn was_truncated(&self) -> bool {
+        self.truncation_reason().is_some()
+    }
+
+    /// Returns the finish reason of the first choice that stopped because of the token
+    /// limit or a content filter.
+    pub fn truncation_reason(&self) -> Option<&FinishReason> {
+        self.choices
+            .iter()
+            .map(|choice| &choice.finish_reason)
+            .find(|reason| matches!(reason, FinishReason::Length | FinishReason::ContentFilter))
+    }
+
+    /// Estimates the cost in USD of the request, based on the model and token usage.
+    ///
+    /// Dated snapshots such as `gpt-4o-2024-08-06` are priced as their base model.
+    /// Returns `None` if the model is unknown. See [`pricing::estimate_cost`].
+    pub fn estimated_cost(&self) -> Option<f64> {
+        let model = Model::from_str(&self.model)
+            .or_else(|_| Model::from_str(strip_snapshot_date(&self.model)))
+            .ok()?;
+        pricing::estimate_cost(&model, &self.usage)
+    }
+
+    /// Returns the `created` Unix timestamp as a UTC date and time.
+    ///
+    /// Returns `None` if the timestamp is out of range. Only available with the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn created_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.created, 0)
+    }
+}
+
+/// Removes a `-YYYY-MM-DD` snapshot date from the end of a model name.
+fn strip_snapshot_date(model: &str) -> &str {
+    let Some(split) = model.len().checked_sub(11) else {
+        return model;
+    };
+    let date = &model.as_bytes()[split..];
+    let is_date = date.iter().enumerate().all(|(i, b)| match i {
+        0 | 5 | 8 => *b == b'-',
+        _ => b.is_ascii_digit(),
+    });
+    // The date is ASCII, so the split is at a character boundary
+    if is_date {
+        &model[..split]
+    } else {
+        model
+    }
 }
 
 /// Represents the usage information in the chat API response.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: i64,
     pub completion_tokens: i64,
     pub total_tokens: i64,
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Represents the breakdown of the completion tokens in the usage information.
+#[derive(Debug, Default, Deserialize, PartialEq, Clone)]
+pub struct CompletionTokensDetails {
+    /// Tokens generated by the model for reasoning.
+    pub reasoning_tokens: Option<i64>,
+    /// Audio tokens generated by the model.
+    pub audio_tokens: Option<i64>,
+    /// Tokens of the predicted output that appeared in the completion.
+    pub accepted_prediction_tokens: Option<i64>,
+    /// Tokens of the predicted output that did not appear in the completion.
+    pub rejected_prediction_tokens: Option<i64>,
+}
+
+/// Represents the breakdown of the prompt tokens in the usage information.
+#[derive(Debug, Default, Deserialize, PartialEq, Clone)]
+pub struct PromptTokensDetails {
+    /// Prompt tokens that were read from the prompt cache.
+    pub cached_tokens: Option<i64>,
+    /// Audio tokens present in the prompt.
+    pub audio_tokens: Option<i64>,
 }
 
 /// Represents a choice in the chat API response.
 #[derive(Debug, Deserialize)]
 pub struct Choice {
     pub message: Message,
-    pub finish_reason: String,
+    pub finish_reason: FinishReason,
+    /// The log probabilities of the output tokens, when requested with `logprobs`.
+    #[serde(default)]
+    pub logprobs: Option<ChoiceLogprobs>,
+}
+
+/// Represents the log probabilities of the tokens of a choice.
+#[derive(Debug, Default, Deserialize, PartialEq, Clone)]
+pub struct ChoiceLogprobs {
+    pub content: Option<Vec<TokenLogprob>>,
+}
+
+/// Represents the log probability of an output token.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// The UTF-8 bytes of the token, as a token may contain part of a character.
+    pub bytes: Option<Vec<u8>>,
+    /// The most likely tokens at this position, when requested with `top_logprobs`.
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+/// Represents one of the most likely tokens at a position.
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+    pub bytes: Option<Vec<u8>>,
 }
 
 /// Represents a message in the chat API call.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Message {
     pub role: Role,
-    pub content: String,
+    /// The content of the message, empty when the API returns `null` for a message with tool calls.
+    #[serde(default, deserialize_with = "deserialize_null_as_default")]
+    pub content: MessageContent,
+    /// Annotations of an assistant message. Only returned by the API, never sent in a request.
+    #[serde(default, skip_serializing)]
+    pub annotations: Option<Vec<Annotation>>,
+    /// The tools the assistant wants to call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// The id of the tool call a tool message responds to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Deserializes `null` as the default value.
+fn deserialize_null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Represents the content of a message, either plain text or a list of parts such as images.
+///
+/// Plain text is serialized as a string, as expected by all models.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::client::{ContentPart, MessageContent};
+///
+/// let text = MessageContent::from("What is in this image?");
+/// assert_eq!(text.as_text(), Some("What is in this image?"));
+///
+/// let parts = MessageContent::from(vec![
+///     ContentPart::text("What is in this image?"),
+///     ContentPart::image_url("https://example.com/image.png"),
+/// ]);
+/// assert_eq!(parts.to_string(), "What is in this image?");
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Returns the text if the content is plain text.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            MessageContent::Text(text) => Some(text),
+            MessageContent::Parts(_) => None,
+        }
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+/// Formats the text of the content, joining the text parts with newlines and skipping images.
+impl fmt::Display for MessageContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageContent::Text(text) => f.write_str(text),
+            MessageContent::Parts(parts) => {
+                let texts: Vec<&str> = parts
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::Text { text } => Some(text.as_str()),
+                        ContentPart::ImageUrl { .. } => None,
+                    })
+                    .collect();
+                f.write_str(&texts.join("\n"))
+            }
+        }
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Parts(parts)
+    }
+}
+
+impl PartialEq<str> for MessageContent {
+    fn eq(&self, other: &str) -> bool {
+        self.as_text() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for MessageContent {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_text() == Some(*other)
+    }
+}
+
+/// Represents a part of the content of a message.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+impl ContentPart {
+    /// Creates a text part.
+    pub fn text(text: &str) -> Self {
+        ContentPart::Text {
+            text: text.to_string(),
+        }
+    }
+
+    /// Creates an image part from a URL, which may also be a base64 `data:` URL.
+    pub fn image_url(url: &str) -> Self {
+        ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: url.to_string(),
+                detail: None,
+            },
+        }
+    }
+}
+
+/// Represents an image, by URL or as a base64 encoded `data:` URL.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ImageUrl {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+}
+
+/// Represents the level of detail the model uses to process an image.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Auto,
+    Low,
+    High,
+}
+
+/// Represents an annotation on an assistant message, such as a web search citation.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A citation of a URL used to generate the message.
+    UrlCitation { url_citation: UrlCitation },
+    /// Any annotation type not known to this crate.
+    #[serde(other)]
+    Other,
+}
+
+/// Represents a URL citation within the content of a message.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UrlCitation {
+    /// The index of the first character of the citation in the message content.
+    pub start_index: usize,
+    /// The index of the last character of the citation in the message content.
+    pub end_index: usize,
+    pub title: String,
+    pub url: String,
+}
+
+/// Represents the web search options for the search-preview models.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WebSearchOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_context_size: Option<SearchContextSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_location: Option<UserLocation>,
+}
+
+/// Represents how much context is retrieved from the web for a search.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchContextSize {
+    Low,
+    Medium,
+    High,
+}
+
+/// Represents the location of the user, used to refine web search results.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum UserLocation {
+    Approximate { approximate: ApproximateLocation },
+}
+
+/// Represents an approximate location of the user.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ApproximateLocation {
+    /// The two-letter ISO country code, e.g. `NL`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    /// The IANA timezone, e.g. `Europe/Amsterdam`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+impl Message {
+    /// Creates a new message with the given role and content.
+    pub fn new(role: Role, content: &str) -> Self {
+        Self::with_content(role, content.into())
+    }
+
+    /// Creates a new message with the given role and content, e.g. text combined with images.
+    pub fn with_content(role: Role, content: MessageContent) -> Self {
+        Self {
+            role,
+            content,
+            annotations: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Creates a tool message with the result of the tool call with the given id.
+    pub fn tool_result(tool_call_id: &str, content: &str) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+
+    /// Creates a message by rendering a template with `{{name}}` placeholders.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if a placeholder has no matching variable.
+    pub fn from_template(
+        role: Role,
+        template: &str,
+        vars: &HashMap<&str, String>,
+    ) -> Result<Self, ChatGPTError> {
+        Ok(Self::new(role, &templates::render(template, vars)?))
+    }
 }
 
 /// Enum representing possible errors in the ChatGPTClient.
@@ -100,106 +788,801 @@ pub enum ChatGPTError {
         body: String,
     },
     #[error("Reqwest error: {0}")]
-    Reqwest(#[from] reqwest::Error),
+    Reqwest(reqwest::Error),
+    #[error("Request timed out: {0}")]
+    Timeout(reqwest::Error),
+    #[error("Connection failed: {0}")]
+    ConnectionError(reqwest::Error),
+    #[error("Authentication failed: {message}")]
+    AuthError { message: String },
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// The time to wait before retrying, from the `Retry-After` header.
+        retry_after: Option<Duration>,
+        message: String,
+        rate_limit: RateLimitInfo,
+    },
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Unexpected response: {0}")]
+    UnexpectedResponse(String),
+    #[error("Response was truncated with finish reason: {finish_reason}")]
+    Truncated {
+        finish_reason: FinishReason,
+        response: Box<ChatResponse>,
+    },
 }
 
-impl ChatGPTClient {
-    /// Creates a new ChatGPTClient with the given API key and base URL.
+/// Builder for a [`ChatGPTClient`] with non-default settings.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::ChatGPTClient;
+///
+/// let client = ChatGPTClient::builder("your_api_key", "https://api.openai.com")
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ClientBuilder {
+    api_key: String,
+    api_key_provider: Option<ApiKeyProvider>,
+    base_url: String,
+    user_agent: String,
+    error_on_truncation: bool,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    max_retry_delay: Duration,
+    timeout: Option<Duration>,
+    tls_backend: TlsBackend,
+    proxy: ProxyConfig,
+    organization: Option<String>,
+    project: Option<String>,
+    default_headers: HeaderMap,
+    /// The first header passed to `with_header` that could not be parsed.
+    invalid_header: Option<String>,
+    #[cfg(feature = "compression")]
+    compression: bool,
+}
+
+/// The TLS implementation used by the underlying HTTP client.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TlsBackend {
+    Rustls,
+    #[cfg(feature = "native-tls")]
+    Native,
+}
+
+/// The proxy used by the underlying HTTP client.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum ProxyConfig {
+    /// Uses the proxy from the environment, e.g. `HTTPS_PROXY`.
+    System,
+    /// Sends all requests through the proxy at the given URL.
+    Url(String),
+    /// Connects directly, ignoring any system proxy.
+    Disabled,
+}
+
+impl ClientBuilder {
+    /// Creates a new ClientBuilder with the given API key and base URL.
     ///
     /// # Arguments
     ///
     /// * `api_key` - The API key for the ChatGPT API.
     /// * `base_url` - The base URL for the ChatGPT API.
     pub fn new(api_key: &str, base_url: &str) -> Self {
-        let client = Client::builder()
-            .use_rustls_tls()
-            .build()
-            .expect("New client");
-
         Self {
-            base_url: base_url.to_string(),
             api_key: api_key.to_string(),
-            client,
+            api_key_provider: None,
+            base_url: base_url.to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            error_on_truncation: false,
+            max_retries: 0,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            max_retry_delay: DEFAULT_MAX_RETRY_DELAY,
+            timeout: None,
+            tls_backend: TlsBackend::Rustls,
+            proxy: ProxyConfig::System,
+            organization: None,
+            project: None,
+            default_headers: HeaderMap::new(),
+            invalid_header: None,
+            #[cfg(feature = "compression")]
+            compression: false,
         }
     }
 
-    /// Sends a request to the ChatGPT API with the given input and returns the response.
+    /// Sets a callback that is called for every request to get the API key.
     ///
-    /// # Arguments
+    /// Use this for short-lived keys that are rotated. The provider takes precedence over
+    /// the static API key passed to [`ClientBuilder::new`], which is used by default.
+    pub fn with_api_key_provider(mut self, provider: ApiKeyProvider) -> Self {
+        self.api_key_provider = Some(provider);
+        self
+    }
+
+    /// Makes `chat` return a ChatGPTError::Truncated when the response was truncated.
     ///
-    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    /// Disabled by default. See [`ChatResponse::was_truncated`].
+    pub fn with_error_on_truncation(mut self, enabled: bool) -> Self {
+        self.error_on_truncation = enabled;
+        self
+    }
+
+    /// Sets how often a request is retried when the API responds with 429 or a 5xx status.
     ///
-    /// # Examples
+    /// Requests are not retried by default. Other errors, such as 400 or 401, are never retried.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the base delay between retries, which is doubled for every attempt and randomized
+    /// with jitter. A `Retry-After` header sent by the API takes precedence.
     ///
-    /// ```
-    /// use chat_gpt_lib_rs::{ChatGPTClient, ChatInput, Message, Model, Role};
+    /// Defaults to [`DEFAULT_RETRY_BASE_DELAY`].
+    pub fn with_retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        self
+    }
+
+    /// Sets the maximum delay between retries. Longer delays, including a `Retry-After`
+    /// sent by the API, are shortened to this maximum.
     ///
-    /// async fn example() {
-    ///     let chat_gpt = ChatGPTClient::new("your_api_key", "https://api.openai.com");
-    ///     let input = ChatInput {
-    ///         model: Model::Gpt_4,
-    ///         messages: vec![
-    ///             Message {
-    ///                 role: Role::System,
-    ///                 content: "You are a helpful assistant.".to_string(),
-    ///             },
-    ///             Message {
-    ///                 role: Role::User,
-    ///                 content: "Who is the best field hockey player in the world".to_string(),
-    ///             },
-    ///         ],
-    ///         ..Default::default()
-    ///     };
+    /// Defaults to [`DEFAULT_MAX_RETRY_DELAY`].
+    pub fn with_max_retry_delay(mut self, delay: Duration) -> Self {
+        self.max_retry_delay = delay;
+        self
+    }
+
+    /// Sets the timeout for each request, from connecting until the response body is read.
+    /// A request that takes longer fails with a ChatGPTError::Timeout.
     ///
-    ///     let response = chat_gpt.chat(input).await.unwrap();
-    /// }
-    /// ```
-    /// # Errors
+    /// By default requests do not time out.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Uses Rustls for TLS, which is the default.
     ///
-    /// Returns a ChatGPTError if the request fails.
+    /// Rustls does not depend on OpenSSL, which allows static builds, e.g. with musl.
+    pub fn with_rustls(mut self) -> Self {
+        self.tls_backend = TlsBackend::Rustls;
+        self
+    }
+
+    /// Uses the platform's native TLS implementation instead of Rustls.
+    ///
+    /// Only available with the `native-tls` feature.
+    #[cfg(feature = "native-tls")]
+    pub fn with_native_tls(mut self) -> Self {
+        self.tls_backend = TlsBackend::Native;
+        self
+    }
+
+    /// Sets the User-Agent header sent with every request.
+    ///
+    /// Defaults to [`DEFAULT_USER_AGENT`].
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Sends all requests through the proxy at the given URL, e.g. `http://proxy.example.com:8080`.
+    ///
+    /// By default the system proxy from the environment is used.
+    pub fn with_proxy(mut self, url: &str) -> Self {
+        self.proxy = ProxyConfig::Url(url.to_string());
+        self
+    }
+
+    /// Connects directly, ignoring any system proxy.
+    pub fn with_no_proxy(mut self) -> Self {
+        self.proxy = ProxyConfig::Disabled;
+        self
+    }
+
+    /// Sets the organization used for requests, sent in the `OpenAI-Organization` header.
+    ///
+    /// Only needed for API keys that belong to multiple organizations.
+    pub fn with_organization(mut self, organization_id: &str) -> Self {
+        self.organization = Some(organization_id.to_string());
+        self
+    }
+
+    /// Sets the project used for requests, sent in the `OpenAI-Project` header.
+    ///
+    /// Only needed for API keys that have access to multiple projects.
+    pub fn with_project(mut self, project_id: &str) -> Self {
+        self.project = Some(project_id.to_string());
+        self
+    }
+
+    /// Adds a header that is sent with every request, e.g. `OpenAI-Beta: assistants=v2`.
+    ///
+    /// An invalid header name or value makes [`ClientBuilder::build`] return a
+    /// ChatGPTError::ConfigError.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.default_headers.insert(name, value);
+            }
+            _ => {
+                self.invalid_header.get_or_insert_with(|| name.to_string());
+            }
+        }
+        self
+    }
+
+    /// Adds headers that are sent with every request.
+    ///
+    /// Headers with the same name as a previously added header replace it, all values of a
+    /// header that is repeated in `headers` are kept.
+    pub fn with_default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers.extend(headers);
+        self
+    }
+
+    /// Enables or disables transparent gzip/brotli decompression of responses.
+    ///
+    /// Compression is disabled by default. Only available with the `compression` feature.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Builds the ChatGPTClient.
+    ///
+    /// The base URL must be an absolute `http` or `https` URL. A trailing slash is
+    /// removed, as endpoint paths are appended to the base URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if the base URL, proxy URL or a header is invalid,
+    /// or a ChatGPTError if the underlying HTTP client cannot be created.
+    pub fn build(self) -> Result<ChatGPTClient, ChatGPTError> {
+        let base_url = validate_base_url(&self.base_url)?;
+        if let Some(name) = self.invalid_header {
+            return Err(ChatGPTError::ConfigError(format!(
+                "Invalid header '{name}'"
+            )));
+        }
+        self.build_with_base_url(base_url)
+    }
+
+    /// Builds the client with an already normalized base URL.
+    fn build_with_base_url(self, base_url: String) -> Result<ChatGPTClient, ChatGPTError> {
+        let builder = Client::builder().user_agent(self.user_agent);
+        let builder = match self.tls_backend {
+            TlsBackend::Rustls => builder.use_rustls_tls(),
+            #[cfg(feature = "native-tls")]
+            TlsBackend::Native => builder.use_native_tls(),
+        };
+        let builder = match &self.proxy {
+            ProxyConfig::System => builder,
+            ProxyConfig::Url(url) => builder.proxy(Proxy::all(url).map_err(|e| {
+                ChatGPTError::ConfigError(format!("Invalid proxy URL '{url}': {e}"))
+            })?),
+            ProxyConfig::Disabled => builder.no_proxy(),
+        };
+
+        let builder = match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+
+        #[cfg(feature = "compression")]
+        let builder = builder.gzip(self.compression).brotli(self.compression);
+
+        Ok(ChatGPTClient {
+            base_url,
+            api_key: self.api_key,
+            api_key_provider: self.api_key_provider,
+            error_on_truncation: self.error_on_truncation,
+            max_retries: self.max_retries,
+            retry_base_delay: self.retry_base_delay,
+            max_retry_delay: self.max_retry_delay,
+            organization: self.organization,
+            project: self.project,
+            default_headers: self.default_headers,
+            client: builder.build()?,
+        })
+    }
+}
+
+/// Checks that the base URL is an absolute `http(s)` URL without a query or fragment,
+/// and returns the parsed URL without a trailing slash.
+fn validate_base_url(base_url: &str) -> Result<String, ChatGPTError> {
+    let url = Url::parse(base_url)
+        .map_err(|e| ChatGPTError::ConfigError(format!("Invalid base URL '{base_url}': {e}")))?;
+
+    if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+        return Err(ChatGPTError::ConfigError(format!(
+            "Invalid base URL '{base_url}': expected an absolute http(s) URL"
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(ChatGPTError::ConfigError(format!(
+            "Invalid base URL '{base_url}': a query or fragment is not allowed"
+        )));
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Broad classification of a ChatGPTError, useful for retry and backoff decisions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorCategory {
+    /// The API key or permissions were rejected (401/403).
+    Auth,
+    /// The request was rate limited (429).
+    RateLimit,
+    /// The request itself was rejected by the API (other 4xx).
+    InvalidRequest,
+    /// The API failed to process a valid request (5xx).
+    Server,
+    /// The request could not be sent or the response could not be received.
+    Network,
+    /// The response body could not be deserialized.
+    Deserialization,
+    /// The client or request was misconfigured.
+    Config,
+}
+
+/// Maps timeouts to ChatGPTError::Timeout and connection failures to
+/// ChatGPTError::ConnectionError, other errors to ChatGPTError::Reqwest.
+impl From<reqwest::Error> for ChatGPTError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ChatGPTError::Timeout(error)
+        } else if error.is_connect() {
+            ChatGPTError::ConnectionError(error)
+        } else {
+            ChatGPTError::Reqwest(error)
+        }
+    }
+}
+
+impl ChatGPTError {
+    /// Returns true if the request timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ChatGPTError::Timeout(_))
+    }
+
+    /// Returns true if the connection to the API could not be established.
+    pub fn is_connect(&self) -> bool {
+        matches!(self, ChatGPTError::ConnectionError(_))
+    }
+
+    /// Returns true if the request may succeed when retried: on rate limits (429),
+    /// server errors (5xx) and timeouts.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ChatGPTError::RequestFailed { status_code, .. } => is_retryable_status(*status_code),
+            ChatGPTError::RateLimited { .. } => true,
+            _ => self.is_timeout(),
+        }
+    }
+
+    /// Returns the HTTP status code of the response that caused this error, if any.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            ChatGPTError::RequestFailed { status_code, .. } => Some(status_code.as_u16()),
+            ChatGPTError::AuthError { .. } => Some(StatusCode::UNAUTHORIZED.as_u16()),
+            ChatGPTError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+            ChatGPTError::Reqwest(e) => e.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Returns the broad category of this error.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ChatGPTError::RequestFailed { status_code, .. } => match *status_code {
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCategory::Auth,
+                StatusCode::TOO_MANY_REQUESTS => ErrorCategory::RateLimit,
+                status if status.is_server_error() => ErrorCategory::Server,
+                _ => ErrorCategory::InvalidRequest,
+            },
+            ChatGPTError::Reqwest(e) if e.is_decode() => ErrorCategory::Deserialization,
+            ChatGPTError::Reqwest(e) if e.is_builder() => ErrorCategory::Config,
+            ChatGPTError::Reqwest(_)
+            | ChatGPTError::Timeout(_)
+            | ChatGPTError::ConnectionError(_) => ErrorCategory::Network,
+            ChatGPTError::AuthError { .. } => ErrorCategory::Auth,
+            ChatGPTError::RateLimited { .. } => ErrorCategory::RateLimit,
+            ChatGPTError::ConfigError(_) | ChatGPTError::Serialization(_) => ErrorCategory::Config,
+            ChatGPTError::Truncated { .. } => ErrorCategory::InvalidRequest,
+            ChatGPTError::UnexpectedResponse(_) => ErrorCategory::Deserialization,
+        }
+    }
+}
+
+impl ChatGPTClient {
+    /// Creates a new ChatGPTClient with the given API key and base URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key for the ChatGPT API.
+    /// * `base_url` - The base URL for the ChatGPT API.
+    ///
+    /// The base URL is not validated, use [`ChatGPTClient::builder`] to reject an invalid
+    /// base URL up front.
+    pub fn new(api_key: &str, base_url: &str) -> Self {
+        ClientBuilder::new(api_key, base_url)
+            .build_with_base_url(base_url.trim_end_matches('/').to_string())
+            .expect("New client")
+    }
+
+    /// Creates a new ChatGPTClient configured from environment variables.
+    ///
+    /// * `OPENAI_API_KEY` - The API key for the ChatGPT API (required).
+    /// * `OPENAI_BASE_URL` - The base URL for the ChatGPT API, defaults to [`DEFAULT_BASE_URL`].
+    /// * `OPENAI_ORG_ID` or `OPENAI_ORGANIZATION` - The organization, see
+    ///   [`ClientBuilder::with_organization`]. `OPENAI_ORG_ID` takes precedence.
+    /// * `OPENAI_PROJECT` - The project, see [`ClientBuilder::with_project`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if `OPENAI_API_KEY` is not set.
+    pub fn from_env() -> Result<Self, ChatGPTError> {
+        let api_key = env::var("OPENAI_API_KEY")
+            .map_err(|_| ChatGPTError::ConfigError("OPENAI_API_KEY is not set".to_string()))?;
+        let base_url = env::var("OPENAI_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+
+        let mut builder = ClientBuilder::new(&api_key, &base_url);
+        if let Ok(organization) =
+            env::var("OPENAI_ORG_ID").or_else(|_| env::var("OPENAI_ORGANIZATION"))
+        {
+            builder = builder.with_organization(&organization);
+        }
+        if let Ok(project) = env::var("OPENAI_PROJECT") {
+            builder = builder.with_project(&project);
+        }
+        builder.build()
+    }
+
+    /// Returns the API key for the next request, from the provider if one is set.
+    fn current_api_key(&self) -> String {
+        match &self.api_key_provider {
+            Some(provider) => provider(),
+            None => self.api_key.clone(),
+        }
+    }
+
+    /// Returns a ClientBuilder for configuring a ChatGPTClient.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key for the ChatGPT API.
+    /// * `base_url` - The base URL for the ChatGPT API.
+    pub fn builder(api_key: &str, base_url: &str) -> ClientBuilder {
+        ClientBuilder::new(api_key, base_url)
+    }
+
+    /// Sends a request to the ChatGPT API with the given input and returns the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chat_gpt_lib_rs::{ChatGPTClient, ChatInput, Message, Model, Role};
+    ///
+    /// async fn example() {
+    ///     let chat_gpt = ChatGPTClient::new("your_api_key", "https://api.openai.com");
+    ///     let input = ChatInput {
+    ///         model: Model::Gpt_4,
+    ///         messages: vec![
+    ///             Message::new(Role::System, "You are a helpful assistant."),
+    ///             Message::new(Role::User, "Who is the best field hockey player in the world"),
+    ///         ],
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let response = chat_gpt.chat(input).await.unwrap();
+    /// }
+    /// ```
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails, or a ChatGPTError::ConfigError if
+    /// the input does not pass [`ChatInput::validate`].
     pub async fn chat(&self, input: ChatInput) -> Result<ChatResponse, ChatGPTError> {
-        let url = format!("{}/v1/chat/completions", self.base_url);
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&input)
-            .send()
-            .await?;
+        self.chat_with_rate_limit(input)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// Sends a request to the ChatGPT API and returns the message of the first choice.
+    ///
+    /// Use [`ChatGPTClient::chat`] to access the full response.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails, or a ChatGPTError::UnexpectedResponse
+    /// if the response contains no choices.
+    pub async fn chat_message(&self, input: ChatInput) -> Result<Message, ChatGPTError> {
+        let response = self.chat(input).await?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| {
+                ChatGPTError::UnexpectedResponse("Response contains no choices".to_string())
+            })
+    }
+
+    /// Sends a request to the ChatGPT API and returns the response together with
+    /// the rate limit information from the response headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails.
+    pub async fn chat_with_rate_limit(
+        &self,
+        input: ChatInput,
+    ) -> Result<(ChatResponse, RateLimitInfo), ChatGPTError> {
+        self.send_chat(input, None).await
+    }
+
+    /// Sends a request to the ChatGPT API using the given API key instead of the client's key.
+    ///
+    /// Useful for services that make requests on behalf of multiple tenants with a single client.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    /// * `api_key` - The API key used for this request only.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails.
+    pub async fn chat_with_key(
+        &self,
+        input: ChatInput,
+        api_key: &str,
+    ) -> Result<ChatResponse, ChatGPTError> {
+        self.send_chat(input, Some(api_key))
+            .await
+            .map(|(response, _)| response)
+    }
 
+    /// Validates and sends a chat request, with the client's API key unless `api_key` is given.
+    async fn send_chat(
+        &self,
+        input: ChatInput,
+        api_key: Option<&str>,
+    ) -> Result<(ChatResponse, RateLimitInfo), ChatGPTError> {
+        input.validate()?;
+
+        let url = self.endpoint_url("/v1/chat/completions");
         debug!(
             "API call to url: {}\n with json payload: {:?}",
             &url, &input
         );
 
-        // Check if the status code is 200
-        if response.status() == StatusCode::OK {
-            response
-                .json::<ChatResponse>()
-                .await
-                .map_err(ChatGPTError::from)
-        } else {
-            let status_code = response.status();
-            let headers = response.headers().clone();
-            let body = response.text().await?;
-            Err(ChatGPTError::RequestFailed {
-                status_code,
-                headers,
-                body,
-            })
+        let (chat_response, rate_limit): (ChatResponse, RateLimitInfo) = self
+            .send(self.client.post(&url).json(&input), api_key)
+            .await?;
+
+        if self.error_on_truncation {
+            if let Some(finish_reason) = chat_response.truncation_reason().cloned() {
+                return Err(ChatGPTError::Truncated {
+                    finish_reason,
+                    response: Box::new(chat_response),
+                });
+            }
+        }
+        Ok((chat_response, rate_limit))
+    }
+
+    /// Sends a POST request with a JSON body to an endpoint not modelled by this crate.
+    ///
+    /// The request is sent with the same headers as [`ChatGPTClient::chat`].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The path relative to the base URL, e.g. `/v1/embeddings`.
+    /// * `body` - The request body, serialized as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails or the response cannot be deserialized into `R`.
+    pub async fn post_raw<T, R>(&self, endpoint: &str, body: &T) -> Result<R, ChatGPTError>
+    where
+        T: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let request = self.client.post(self.endpoint_url(endpoint)).json(body);
+        self.send(request, None).await.map(|(response, _)| response)
+    }
+
+    /// Sends a GET request to an endpoint not modelled by this crate.
+    ///
+    /// The request is sent with the same headers as [`ChatGPTClient::chat`].
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The path relative to the base URL, e.g. `/v1/models`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails or the response cannot be deserialized into `R`.
+    pub async fn get_raw<R: DeserializeOwned>(&self, endpoint: &str) -> Result<R, ChatGPTError> {
+        let request = self.client.get(self.endpoint_url(endpoint));
+        self.send(request, None).await.map(|(response, _)| response)
+    }
+
+    /// Returns the full URL for an endpoint path relative to the base URL.
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'))
+    }
+
+    /// Sends a request with the authorization header and deserializes the JSON response.
+    ///
+    /// The given API key is used instead of the client's key when it is set.
+    ///
+    /// Responses with status 429 or 5xx are retried up to the configured number of retries.
+    async fn send<R: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        api_key: Option<&str>,
+    ) -> Result<(R, RateLimitInfo), ChatGPTError> {
+        let api_key = match api_key {
+            Some(api_key) => api_key.to_string(),
+            None => self.current_api_key(),
+        };
+        let mut request = request.header("Authorization", format!("Bearer {api_key}"));
+        if let Some(organization) = &self.organization {
+            request = request.header("OpenAI-Organization", organization);
+        }
+        if let Some(project) = &self.project {
+            request = request.header("OpenAI-Project", project);
+        }
+        let request = request.headers(self.default_headers.clone());
+
+        let mut attempt = 0;
+        loop {
+            // Requests with a streaming body cannot be cloned, these are sent only once
+            let retry_request = if attempt < self.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let response = match retry_request {
+                Some(retry_request) => retry_request.send().await?,
+                None => return parse_response(request.send().await?).await,
+            };
+
+            let status = response.status();
+            if !is_retryable_status(status) {
+                return parse_response(response).await;
+            }
+
+            let delay = rate_limit::retry_after(response.headers())
+                .unwrap_or_else(|| backoff_delay(self.retry_base_delay, attempt))
+                .min(self.max_retry_delay);
+            attempt += 1;
+            debug!(
+                "Request failed with status {}, retry {} of {} in {:?}",
+                status, attempt, self.max_retries, delay
+            );
+            tokio::time::sleep(delay).await;
         }
     }
 }
 
+/// Deserializes a successful response, or converts an unsuccessful response into a ChatGPTError.
+async fn parse_response<R: DeserializeOwned>(
+    response: Response,
+) -> Result<(R, RateLimitInfo), ChatGPTError> {
+    if response.status().is_success() {
+        let rate_limit = RateLimitInfo::from_headers(response.headers());
+        Ok((response.json::<R>().await?, rate_limit))
+    } else {
+        Err(error_from_response(response).await)
+    }
+}
+
+/// Returns true if a request with this response status may succeed when retried.
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Returns `base * 2^attempt`, plus a random jitter of up to half that delay.
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let delay = base.saturating_mul(2u32.saturating_pow(attempt));
+    let random = RandomState::new().build_hasher().finish();
+    let jitter = delay.mul_f64(random as f64 / u64::MAX as f64 / 2.0);
+    delay.saturating_add(jitter)
+}
+
+/// Represents the error body returned by the API.
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    error: ApiErrorDetail,
+}
+
+/// Represents the details of an error returned by the API.
+#[derive(Debug, Deserialize)]
+struct ApiErrorDetail {
+    message: String,
+}
+
+/// Converts an unsuccessful response into a ChatGPTError.
+async fn error_from_response(response: Response) -> ChatGPTError {
+    let status_code = response.status();
+    let headers = response.headers().clone();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return ChatGPTError::from(e),
+    };
+
+    let error_message = |body: String| {
+        serde_json::from_str::<ApiErrorBody>(&body)
+            .map(|error_body| error_body.error.message)
+            .unwrap_or(body)
+    };
+
+    match status_code {
+        StatusCode::UNAUTHORIZED => ChatGPTError::AuthError {
+            message: error_message(body),
+        },
+        StatusCode::TOO_MANY_REQUESTS => ChatGPTError::RateLimited {
+            retry_after: rate_limit::retry_after(&headers),
+            message: error_message(body),
+            rate_limit: RateLimitInfo::from_headers(&headers),
+        },
+        _ => ChatGPTError::RequestFailed {
+            status_code,
+            headers,
+            body,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{
+        chat_response, chat_response_json, chat_response_with_choices, chat_success,
+        mount_chat_success,
+    };
+    use serial_test::serial;
 
     // Helper function to create a ChatGPTClient instance with a dummy API key and base URL
     fn create_dummy_client() -> ChatGPTClient {
         ChatGPTClient::new("dummy_api_key", "https://dummy-api-url.com")
     }
 
+    // Helper function returning a minimal chat input
+    fn sample_chat_input() -> ChatInput {
+        ChatInput {
+            model: Model::Gpt_4o,
+            messages: vec![Message::new(Role::User, "Hello")],
+            ..Default::default()
+        }
+    }
+
     #[tokio::test]
     async fn test_chat_gpt_client_new() {
         let client = create_dummy_client();
@@ -216,14 +1599,11 @@ mod tests {
         let input = ChatInput {
             model: Model::Gpt_4,
             messages: vec![
-                Message {
-                    role: Role::System,
-                    content: "You are a helpful assistant.".to_string(),
-                },
-                Message {
-                    role: Role::User,
-                    content: "Who is the best field hockey player in the world?".to_string(),
-                },
+                Message::new(Role::System, "You are a helpful assistant."),
+                Message::new(
+                    Role::User,
+                    "Who is the best field hockey player in the world?",
+                ),
             ],
             ..Default::default()
         };
@@ -238,6 +1618,7 @@ mod tests {
             prompt_tokens: 10,
             completion_tokens: 20,
             total_tokens: 30,
+            ..Default::default()
         };
 
         assert_eq!(usage.prompt_tokens, 10);
@@ -248,15 +1629,1660 @@ mod tests {
     #[test]
     fn test_choice_struct() {
         let choice = Choice {
-            message: Message {
-                role: Role::Assistant,
-                content: "Sample response".to_string(),
-            },
-            finish_reason: "stop".to_string(),
+            message: Message::new(Role::Assistant, "Sample response"),
+            finish_reason: FinishReason::Stop,
+            logprobs: None,
         };
 
         assert_eq!(choice.message.role, Role::Assistant);
         assert_eq!(choice.message.content, "Sample response");
-        assert_eq!(choice.finish_reason, "stop");
+        assert_eq!(choice.finish_reason, FinishReason::Stop);
+    }
+
+    #[tokio::test]
+    async fn test_chat_success() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("Authorization", "Bearer dummy_api_key"))
+            .respond_with(chat_success())
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let response = client.chat(sample_chat_input()).await.unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+        assert_eq!(response.usage.total_tokens, 21);
+        assert_eq!(response.choices[0].message.content, "Hello there!");
+        assert_eq!(response.choices[0].finish_reason, FinishReason::Stop);
+    }
+
+    #[test]
+    fn test_chat_input_store_and_metadata_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        assert!(json.get("store").is_none());
+        assert!(json.get("metadata").is_none());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("project".to_string(), "demo".to_string());
+        let input = ChatInput {
+            store: Some(true),
+            metadata: Some(metadata),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+        assert_eq!(json["store"], serde_json::json!(true));
+        assert_eq!(json["metadata"], serde_json::json!({"project": "demo"}));
+    }
+
+    #[test]
+    fn test_chat_input_service_tier_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        assert!(json.get("service_tier").is_none());
+
+        let input = ChatInput {
+            service_tier: Some(ServiceTier::Flex),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+        assert_eq!(json["service_tier"], serde_json::json!("flex"));
+    }
+
+    #[test]
+    fn test_chat_response_service_tier_deserialization() {
+        let response = chat_response();
+        assert_eq!(response.service_tier, None);
+
+        let mut json = chat_response_json();
+        json["service_tier"] = serde_json::json!("default");
+        let response: ChatResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(response.service_tier, Some(ServiceTier::Default));
+    }
+
+    // Helper function to create a RequestFailed error with the given status code
+    fn request_failed(status_code: StatusCode) -> ChatGPTError {
+        ChatGPTError::RequestFailed {
+            status_code,
+            headers: HeaderMap::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_error_category_from_status() {
+        assert_eq!(
+            request_failed(StatusCode::UNAUTHORIZED).category(),
+            ErrorCategory::Auth
+        );
+        assert_eq!(
+            request_failed(StatusCode::FORBIDDEN).category(),
+            ErrorCategory::Auth
+        );
+        assert_eq!(
+            request_failed(StatusCode::TOO_MANY_REQUESTS).category(),
+            ErrorCategory::RateLimit
+        );
+        assert_eq!(
+            request_failed(StatusCode::BAD_REQUEST).category(),
+            ErrorCategory::InvalidRequest
+        );
+        assert_eq!(
+            request_failed(StatusCode::SERVICE_UNAVAILABLE).category(),
+            ErrorCategory::Server
+        );
+    }
+
+    #[test]
+    fn test_error_is_retryable_and_status_code() {
+        for (status, retryable) in [
+            (StatusCode::BAD_REQUEST, false),
+            (StatusCode::UNAUTHORIZED, false),
+            (StatusCode::NOT_FOUND, false),
+            (StatusCode::TOO_MANY_REQUESTS, true),
+            (StatusCode::INTERNAL_SERVER_ERROR, true),
+            (StatusCode::SERVICE_UNAVAILABLE, true),
+        ] {
+            let error = request_failed(status);
+            assert_eq!(error.is_retryable(), retryable, "{status}");
+            assert_eq!(error.status_code(), Some(status.as_u16()));
+        }
+
+        let error = ChatGPTError::RateLimited {
+            retry_after: None,
+            message: "Rate limit reached".to_string(),
+            rate_limit: RateLimitInfo::default(),
+        };
+        assert!(error.is_retryable());
+        assert_eq!(error.status_code(), Some(429));
+
+        let error = ChatGPTError::AuthError {
+            message: "Invalid API key".to_string(),
+        };
+        assert!(!error.is_retryable());
+        assert_eq!(error.status_code(), Some(401));
+
+        let error = ChatGPTError::ConfigError("Invalid base URL".to_string());
+        assert!(!error.is_retryable());
+        assert_eq!(error.status_code(), None);
+    }
+
+    #[test]
+    fn test_error_category_from_reqwest() {
+        let builder_error = Client::new().get("not a url").build().unwrap_err();
+        assert_eq!(
+            ChatGPTError::from(builder_error).category(),
+            ErrorCategory::Config
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_category_network_and_deserialization() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // Nothing listens on port 1, so the request itself fails
+        let client = ChatGPTClient::new("dummy_api_key", "http://127.0.0.1:1");
+        let result = client.chat(sample_chat_input()).await;
+        assert_eq!(result.unwrap_err().category(), ErrorCategory::Network);
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("not json", "application/json"))
+            .mount(&server)
+            .await;
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let result = client.chat(sample_chat_input()).await;
+        assert_eq!(
+            result.unwrap_err().category(),
+            ErrorCategory::Deserialization
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_reads_key_and_base_url() {
+        for name in ["OPENAI_ORG_ID", "OPENAI_ORGANIZATION", "OPENAI_PROJECT"] {
+            env::remove_var(name);
+        }
+        env::set_var("OPENAI_API_KEY", "env_api_key");
+        env::set_var("OPENAI_BASE_URL", "https://env-api-url.com");
+
+        let client = ChatGPTClient::from_env().unwrap();
+        assert_eq!(client.api_key, "env_api_key");
+        assert_eq!(client.base_url, "https://env-api-url.com");
+
+        env::remove_var("OPENAI_BASE_URL");
+        let client = ChatGPTClient::from_env().unwrap();
+        assert_eq!(client.base_url, DEFAULT_BASE_URL);
+        assert_eq!(client.organization, None);
+        assert_eq!(client.project, None);
+
+        env::remove_var("OPENAI_API_KEY");
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_reads_organization_and_project() {
+        env::set_var("OPENAI_API_KEY", "env_api_key");
+        env::set_var("OPENAI_ORGANIZATION", "org-fallback");
+        env::set_var("OPENAI_PROJECT", "proj_456");
+
+        let client = ChatGPTClient::from_env().unwrap();
+        assert_eq!(client.organization.as_deref(), Some("org-fallback"));
+        assert_eq!(client.project.as_deref(), Some("proj_456"));
+
+        env::set_var("OPENAI_ORG_ID", "org-123");
+        let client = ChatGPTClient::from_env().unwrap();
+        assert_eq!(client.organization.as_deref(), Some("org-123"));
+
+        for name in [
+            "OPENAI_API_KEY",
+            "OPENAI_ORG_ID",
+            "OPENAI_ORGANIZATION",
+            "OPENAI_PROJECT",
+        ] {
+            env::remove_var(name);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_env_missing_api_key() {
+        env::remove_var("OPENAI_API_KEY");
+
+        let result = ChatGPTClient::from_env();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_rate_limit() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                chat_success()
+                    .insert_header("x-ratelimit-limit-requests", "60")
+                    .insert_header("x-ratelimit-remaining-requests", "59")
+                    .insert_header("x-ratelimit-reset-tokens", "6m0s"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let (response, rate_limit) = client
+            .chat_with_rate_limit(sample_chat_input())
+            .await
+            .unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+        assert_eq!(rate_limit.limit_requests, Some(60));
+        assert_eq!(rate_limit.remaining_requests, Some(59));
+        assert_eq!(rate_limit.remaining_tokens, None);
+        assert_eq!(
+            rate_limit.reset_tokens,
+            Some(std::time::Duration::from_secs(360))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_stream() {
+        let client = create_dummy_client();
+        let input = ChatInput {
+            stream: Some(true),
+            ..sample_chat_input()
+        };
+
+        let result = client.chat(input).await;
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    // Helper function to create a chat input with the given stop sequences
+    fn chat_input_with_stop(stop: &[&str]) -> ChatInput {
+        ChatInput {
+            stop: Some(stop.iter().map(|s| s.to_string()).collect()),
+            ..sample_chat_input()
+        }
+    }
+
+    #[test]
+    fn test_validate_stop_sequences() {
+        assert!(sample_chat_input().validate().is_ok());
+        assert!(chat_input_with_stop(&["a", "b", "c", "d"])
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            chat_input_with_stop(&["a", "b", "c", "d", "e"]).validate(),
+            Err(ChatGPTError::ConfigError(_))
+        ));
+        assert!(matches!(
+            chat_input_with_stop(&["a", ""]).validate(),
+            Err(ChatGPTError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_chat_input_to_json() {
+        let input = ChatInput {
+            temperature: Some(0.5),
+            ..sample_chat_input()
+        };
+        let expected = r#"{
+  "model": "gpt-4o",
+  "messages": [
+    {
+      "role": "user",
+      "content": "Hello"
+    }
+  ],
+  "temperature": 0.5
+}"#;
+
+        assert_eq!(input.to_json().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_chat_input_to_request_body() {
+        assert_eq!(
+            sample_chat_input().to_request_body().unwrap(),
+            r#"{"model":"gpt-4o","messages":[{"role":"user","content":"Hello"}]}"#
+        );
+
+        let input = ChatInput {
+            temperature: Some(0.5),
+            seed: Some(42),
+            stop: Some(vec!["END".to_string()]),
+            ..sample_chat_input()
+        };
+        assert_eq!(
+            input.to_request_body().unwrap(),
+            r#"{"model":"gpt-4o","messages":[{"role":"user","content":"Hello"}],"temperature":0.5,"stop":["END"],"seed":42}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_request_body() {
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(chat_success())
+            .mount(&server)
+            .await;
+
+        let input = ChatInput {
+            temperature: Some(0.5),
+            ..sample_chat_input()
+        };
+        let expected = input.to_request_body().unwrap();
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        client.chat(input).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&requests[0].body), expected);
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_user_agent() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("User-Agent", DEFAULT_USER_AGENT))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("User-Agent", "my-app/1.0"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let default_client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        assert!(default_client.chat(sample_chat_input()).await.is_ok());
+
+        let custom_client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_user_agent("my-app/1.0")
+            .build()
+            .unwrap();
+        assert!(custom_client.chat(sample_chat_input()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_chat_input_deserialize_and_send() {
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer};
+
+        let preset = serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "temperature": 0.2,
+            "stop": ["END"]
+        });
+        let input: ChatInput = serde_json::from_value(preset.clone()).unwrap();
+        assert_eq!(input.model, Model::Gpt_4o);
+        assert_eq!(input.temperature, Some(0.2));
+        assert_eq!(input.stop, Some(vec!["END".to_string()]));
+        assert_eq!(input.max_tokens, None);
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_json(&preset))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        assert!(client.chat(input).await.is_ok());
+    }
+
+    #[test]
+    fn test_client_builder_rejects_schemeless_base_url() {
+        let result = ChatGPTClient::builder("dummy_api_key", "api.openai.com").build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+
+        let result = ChatGPTClient::builder("dummy_api_key", "localhost:8080").build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_client_builder_rejects_query_and_fragment() {
+        let result = ChatGPTClient::builder("dummy_api_key", "https://host/proxy?k=1").build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+
+        let result = ChatGPTClient::builder("dummy_api_key", "https://host/proxy#top").build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_client_builder_trims_surrounding_whitespace() {
+        let client = ChatGPTClient::builder("dummy_api_key", " https://api.openai.com/ ")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "https://api.openai.com");
+        assert_eq!(
+            client.endpoint_url("v1/chat/completions"),
+            "https://api.openai.com/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_chat_gpt_client_new_does_not_validate_base_url() {
+        let client = ChatGPTClient::new("dummy_api_key", "api.openai.com/");
+        assert_eq!(client.base_url, "api.openai.com");
+    }
+
+    #[test]
+    fn test_client_builder_normalizes_trailing_slash() {
+        let client = ChatGPTClient::builder("dummy_api_key", "https://api.openai.com/")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "https://api.openai.com");
+
+        let client = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL)
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, DEFAULT_BASE_URL);
+    }
+
+    #[test]
+    fn test_endpoint_url_with_and_without_trailing_slash() {
+        for base_url in [
+            "https://proxy.example.com/openai",
+            "https://proxy.example.com/openai/",
+        ] {
+            let client = ChatGPTClient::builder("dummy_api_key", base_url)
+                .build()
+                .unwrap();
+            assert_eq!(
+                client.endpoint_url("/v1/chat/completions"),
+                "https://proxy.example.com/openai/v1/chat/completions"
+            );
+            assert_eq!(
+                client.endpoint_url("v1/chat/completions"),
+                "https://proxy.example.com/openai/v1/chat/completions"
+            );
+        }
+    }
+
+    #[test]
+    fn test_message_from_template() {
+        let vars = HashMap::from([("sport", "field hockey".to_string())]);
+
+        let message =
+            Message::from_template(Role::User, "Who is the best {{sport}} player?", &vars).unwrap();
+        assert_eq!(message.role, Role::User);
+        assert_eq!(message.content, "Who is the best field hockey player?");
+
+        let result = Message::from_template(Role::User, "{{unknown}}", &vars);
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_error_is_connect() {
+        // Nothing listens on port 1, so the connection is refused
+        let client = ChatGPTClient::new("dummy_api_key", "http://127.0.0.1:1");
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+
+        assert!(matches!(error, ChatGPTError::ConnectionError(_)));
+        assert!(error.is_connect());
+        assert!(!error.is_timeout());
+        assert_eq!(error.category(), ErrorCategory::Network);
+        assert!(!request_failed(StatusCode::INTERNAL_SERVER_ERROR).is_connect());
+    }
+
+    #[tokio::test]
+    async fn test_error_is_timeout() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(chat_success().set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_timeout(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+
+        assert!(matches!(error, ChatGPTError::Timeout(_)));
+        assert!(error.is_timeout());
+        assert_eq!(error.category(), ErrorCategory::Network);
+        assert!(error.is_retryable());
+        assert!(!error.is_connect());
+        assert!(!request_failed(StatusCode::GATEWAY_TIMEOUT).is_timeout());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chat_response_created_datetime() {
+        let response = chat_response();
+        let created = response.created_datetime().unwrap();
+
+        assert_eq!(created.to_rfc3339(), "2023-03-01T06:31:28+00:00");
+        assert_eq!(response.created, 1677652288);
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_api_key_provider() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        for key in ["rotated-key-1", "rotated-key-2"] {
+            Mock::given(method("POST"))
+                .and(header("Authorization", format!("Bearer {key}").as_str()))
+                .respond_with(chat_success())
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider_calls = Arc::clone(&calls);
+        let client = ChatGPTClient::builder("static-key", &server.uri())
+            .with_api_key_provider(Arc::new(move || {
+                let call = provider_calls.fetch_add(1, Ordering::SeqCst) + 1;
+                format!("rotated-key-{call}")
+            }))
+            .build()
+            .unwrap();
+
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    // Helper function returning the sample response body with the given finish reason
+    fn sample_chat_response_with_finish_reason(finish_reason: &str) -> serde_json::Value {
+        let mut json = chat_response_json();
+        json["choices"][0]["finish_reason"] = serde_json::json!(finish_reason);
+        json
+    }
+
+    #[test]
+    fn test_chat_response_was_truncated() {
+        for (finish_reason, truncated) in [
+            ("stop", false),
+            ("tool_calls", false),
+            ("length", true),
+            ("content_filter", true),
+        ] {
+            let response: ChatResponse =
+                serde_json::from_value(sample_chat_response_with_finish_reason(finish_reason))
+                    .unwrap();
+            assert_eq!(response.was_truncated(), truncated, "{finish_reason}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_error_on_truncation() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(sample_chat_response_with_finish_reason("length")),
+            )
+            .mount(&server)
+            .await;
+
+        let default_client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let response = default_client.chat(sample_chat_input()).await.unwrap();
+        assert!(response.was_truncated());
+
+        let strict_client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_error_on_truncation(true)
+            .build()
+            .unwrap();
+        let result = strict_client.chat(sample_chat_input()).await;
+        assert!(matches!(
+            result,
+            Err(ChatGPTError::Truncated {
+                finish_reason: FinishReason::Length,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chat_input_prediction_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        assert!(json.get("prediction").is_none());
+
+        let input = ChatInput {
+            prediction: Some(Prediction::Content {
+                content: "fn main() {}".to_string(),
+            }),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+        assert_eq!(
+            json["prediction"],
+            serde_json::json!({"type": "content", "content": "fn main() {}"})
+        );
+    }
+
+    #[test]
+    fn test_usage_prediction_tokens_deserialization() {
+        let usage: Usage = serde_json::from_str(
+            r#"{
+                "prompt_tokens": 20,
+                "completion_tokens": 30,
+                "total_tokens": 50,
+                "completion_tokens_details": {
+                    "accepted_prediction_tokens": 18,
+                    "rejected_prediction_tokens": 4
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            usage.completion_tokens_details,
+            Some(CompletionTokensDetails {
+                accepted_prediction_tokens: Some(18),
+                rejected_prediction_tokens: Some(4),
+                ..Default::default()
+            })
+        );
+
+        let usage: Usage = serde_json::from_str(
+            r#"{"prompt_tokens": 1, "completion_tokens": 2, "total_tokens": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(usage.completion_tokens_details, None);
+    }
+
+    #[test]
+    fn test_usage_token_details_deserialization() {
+        let usage: Usage = serde_json::from_str(
+            r#"{
+                "prompt_tokens": 1200,
+                "completion_tokens": 900,
+                "total_tokens": 2100,
+                "prompt_tokens_details": {"cached_tokens": 1024, "audio_tokens": 0},
+                "completion_tokens_details": {
+                    "reasoning_tokens": 640,
+                    "audio_tokens": 0,
+                    "accepted_prediction_tokens": 0,
+                    "rejected_prediction_tokens": 0
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let prompt_details = usage.prompt_tokens_details.unwrap();
+        assert_eq!(prompt_details.cached_tokens, Some(1024));
+        assert_eq!(prompt_details.audio_tokens, Some(0));
+
+        let completion_details = usage.completion_tokens_details.unwrap();
+        assert_eq!(completion_details.reasoning_tokens, Some(640));
+        assert_eq!(completion_details.audio_tokens, Some(0));
+    }
+
+    #[test]
+    fn test_chat_input_web_search_options_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        assert!(json.get("web_search_options").is_none());
+
+        let input = ChatInput {
+            model: Model::Gpt_4oSearchPreview,
+            web_search_options: Some(WebSearchOptions {
+                search_context_size: Some(SearchContextSize::Low),
+                user_location: Some(UserLocation::Approximate {
+                    approximate: ApproximateLocation {
+                        country: Some("NL".to_string()),
+                        city: Some("Amsterdam".to_string()),
+                        ..Default::default()
+                    },
+                }),
+            }),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+
+        assert_eq!(json["model"], serde_json::json!("gpt-4o-search-preview"));
+        assert_eq!(
+            json["web_search_options"],
+            serde_json::json!({
+                "search_context_size": "low",
+                "user_location": {
+                    "type": "approximate",
+                    "approximate": {"country": "NL", "city": "Amsterdam"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_annotations_deserialization() {
+        let message: Message = serde_json::from_str(
+            r#"{
+                "role": "assistant",
+                "content": "See the rules of field hockey.",
+                "annotations": [
+                    {
+                        "type": "url_citation",
+                        "url_citation": {
+                            "start_index": 4,
+                            "end_index": 29,
+                            "title": "Rules of hockey",
+                            "url": "https://example.com/rules"
+                        }
+                    },
+                    {"type": "something_new"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            message.annotations,
+            Some(vec![
+                Annotation::UrlCitation {
+                    url_citation: UrlCitation {
+                        start_index: 4,
+                        end_index: 29,
+                        title: "Rules of hockey".to_string(),
+                        url: "https://example.com/rules".to_string(),
+                    },
+                },
+                Annotation::Other,
+            ])
+        );
+
+        let json = serde_json::to_value(Message::new(Role::User, "Hi")).unwrap();
+        assert!(json.get("annotations").is_none());
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert!(json.get("annotations").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_chat_message() {
+        use wiremock::MockServer;
+
+        let server = MockServer::start().await;
+        mount_chat_success(&server).await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let message = client.chat_message(sample_chat_input()).await.unwrap();
+
+        assert_eq!(message.role, Role::Assistant);
+        assert_eq!(message.content, "Hello there!");
+    }
+
+    #[tokio::test]
+    async fn test_chat_message_without_choices() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mut json = chat_response_json();
+        json["choices"] = serde_json::json!([]);
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json))
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let result = client.chat_message(sample_chat_input()).await;
+
+        assert!(matches!(result, Err(ChatGPTError::UnexpectedResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chat_invalid_api_key() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+                "error": {
+                    "message": "Incorrect API key provided: dummy_api_key.",
+                    "type": "invalid_request_error",
+                    "param": null,
+                    "code": "invalid_api_key"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+
+        assert_eq!(error.category(), ErrorCategory::Auth);
+        match error {
+            ChatGPTError::AuthError { message } => {
+                assert_eq!(message, "Incorrect API key provided: dummy_api_key.")
+            }
+            other => panic!("Expected AuthError, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_request_failed() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            ChatGPTError::RequestFailed { status_code: StatusCode::BAD_REQUEST, ref body, .. }
+                if body == "bad request"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_post_raw_and_get_raw() {
+        use wiremock::matchers::{body_json, header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[derive(Debug, Deserialize)]
+        struct Embeddings {
+            data: Vec<EmbeddingData>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct EmbeddingData {
+            embedding: Vec<f32>,
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/embeddings"))
+            .and(header("Authorization", "Bearer dummy_api_key"))
+            .and(body_json(serde_json::json!({"input": "Hello"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"embedding": [0.5, -0.25]}]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models/gpt-4o"))
+            .and(header("Authorization", "Bearer dummy_api_key"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "gpt-4o"})),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+
+        let embeddings: Embeddings = client
+            .post_raw("/v1/embeddings", &serde_json::json!({"input": "Hello"}))
+            .await
+            .unwrap();
+        assert_eq!(embeddings.data[0].embedding, vec![0.5, -0.25]);
+
+        let model: serde_json::Value = client.get_raw("v1/models/gpt-4o").await.unwrap();
+        assert_eq!(model["id"], "gpt-4o");
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_explicit_tls_backend() {
+        use wiremock::MockServer;
+
+        let server = MockServer::start().await;
+        mount_chat_success(&server).await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_rustls()
+            .build()
+            .unwrap();
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+
+        #[cfg(feature = "native-tls")]
+        {
+            let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+                .with_native_tls()
+                .build()
+                .unwrap();
+            assert!(client.chat(sample_chat_input()).await.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_chat_input_tools_serialization() {
+        use crate::tools::Tool;
+
+        let input = ChatInput {
+            tools: Some(vec![Tool::function(
+                "get_weather",
+                "Get the weather",
+                serde_json::json!({"type": "object"}),
+            )]),
+            tool_choice: Some(ToolChoice::Auto),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+
+        assert_eq!(json["tools"][0]["type"], "function");
+        assert_eq!(json["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(json["tool_choice"], "auto");
+
+        let json = serde_json::to_value(Message::tool_result("call_1", "Sunny")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"role": "tool", "content": "Sunny", "tool_call_id": "call_1"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tool_calls() {
+        use crate::tools::ToolType;
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chat_response_with_choices(
+                    serde_json::json!([{
+                        "index": 0,
+                        "message": {
+                            "role": "assistant",
+                            "content": null,
+                            "tool_calls": [{
+                                "id": "call_abc",
+                                "type": "function",
+                                "function": {
+                                    "name": "get_weather",
+                                    "arguments": "{\"city\":\"Amsterdam\"}"
+                                }
+                            }]
+                        },
+                        "finish_reason": "tool_calls"
+                    }]),
+                )),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let response = client.chat(sample_chat_input()).await.unwrap();
+        let choice = &response.choices[0];
+        let tool_call = &choice.message.tool_calls.as_ref().unwrap()[0];
+
+        assert_eq!(choice.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(choice.message.content, MessageContent::default());
+        assert_eq!(tool_call.id, "call_abc");
+        assert_eq!(tool_call.tool_type, ToolType::Function);
+        assert_eq!(tool_call.function.name, "get_weather");
+        assert_eq!(tool_call.function.arguments, r#"{"city":"Amsterdam"}"#);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let base = Duration::from_millis(100);
+        for attempt in 0..4 {
+            let expected = base * 2u32.pow(attempt);
+            let delay = backoff_delay(base, attempt);
+            assert!(delay >= expected && delay <= expected + expected / 2);
+        }
+
+        assert_eq!(backoff_delay(Duration::MAX, 40), Duration::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_chat_retries_rate_limited_and_server_errors() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after-ms", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_max_retries(2)
+            .with_retry_base_delay(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let response = client.chat(sample_chat_input()).await.unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+    }
+
+    #[tokio::test]
+    async fn test_chat_clamps_retry_after_to_max_retry_delay() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_max_retries(1)
+            .with_max_retry_delay(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let response =
+            tokio::time::timeout(Duration::from_secs(5), client.chat(sample_chat_input()))
+                .await
+                .expect("The retry delay is clamped")
+                .unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+    }
+
+    #[tokio::test]
+    async fn test_chat_retries_with_overflowing_retry_after() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1e20"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_max_retries(1)
+            .with_retry_base_delay(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let response = client.chat(sample_chat_input()).await.unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+    }
+
+    #[tokio::test]
+    async fn test_chat_gives_up_after_max_retries() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_max_retries(2)
+            .with_retry_base_delay(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+
+        assert_eq!(error.category(), ErrorCategory::Server);
+    }
+
+    #[tokio::test]
+    async fn test_chat_does_not_retry_client_errors() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        for status in [400, 401, 404] {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(status))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+                .with_max_retries(3)
+                .with_retry_base_delay(Duration::from_millis(1))
+                .build()
+                .unwrap();
+
+            assert!(client.chat(sample_chat_input()).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_rate_limited_error() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("retry-after", "5")
+                    .insert_header("x-ratelimit-remaining-requests", "0")
+                    .insert_header("x-ratelimit-reset-tokens", "1s")
+                    .set_body_json(serde_json::json!({
+                        "error": {"message": "Rate limit reached for requests"}
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let error = client.chat(sample_chat_input()).await.unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::RateLimit);
+
+        match error {
+            ChatGPTError::RateLimited {
+                retry_after,
+                message,
+                rate_limit,
+            } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(5)));
+                assert_eq!(message, "Rate limit reached for requests");
+                assert_eq!(rate_limit.remaining_requests, Some(0));
+                assert_eq!(rate_limit.reset_tokens, Some(Duration::from_secs(1)));
+            }
+            other => panic!("Expected a RateLimited error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chat_response_format_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        assert!(json.get("response_format").is_none());
+
+        assert_eq!(
+            serde_json::to_value(ChatResponseFormat::Text).unwrap(),
+            serde_json::json!({"type": "text"})
+        );
+
+        let format = ChatResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "answer".to_string(),
+                description: None,
+                schema: Some(serde_json::json!({"type": "object"})),
+                strict: Some(true),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(format).unwrap(),
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "answer", "schema": {"type": "object"}, "strict": true}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_response_format() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({
+                "response_format": {"type": "json_object"}
+            })))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let input = ChatInput {
+            response_format: Some(ChatResponseFormat::JsonObject),
+            ..sample_chat_input()
+        };
+
+        assert!(client.chat(input).await.is_ok());
+    }
+
+    #[test]
+    fn test_client_builder_proxy() {
+        let builder = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL);
+        assert_eq!(builder.proxy, ProxyConfig::System);
+
+        let builder = builder.with_proxy("http://proxy.example.com:8080");
+        assert_eq!(
+            builder.proxy,
+            ProxyConfig::Url("http://proxy.example.com:8080".to_string())
+        );
+        assert!(builder.build().is_ok());
+
+        let builder = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL).with_no_proxy();
+        assert_eq!(builder.proxy, ProxyConfig::Disabled);
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn test_client_builder_rejects_invalid_proxy() {
+        let result = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL)
+            .with_proxy("not a valid proxy")
+            .build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_custom_headers() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("Authorization", "Bearer dummy_api_key"))
+            .and(header("OpenAI-Beta", "assistants=v2"))
+            .and(header("X-Request-Source", "tests"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Request-Source", HeaderValue::from_static("tests"));
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_header("OpenAI-Beta", "assistants=v2")
+            .with_default_headers(headers)
+            .build()
+            .unwrap();
+
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+    }
+
+    #[test]
+    fn test_client_builder_keeps_repeated_default_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("X-Tag", HeaderValue::from_static("first"));
+        headers.append("X-Tag", HeaderValue::from_static("second"));
+        let client = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL)
+            .with_header("X-Tag", "replaced")
+            .with_default_headers(headers)
+            .build()
+            .unwrap();
+
+        let values: Vec<_> = client.default_headers.get_all("X-Tag").iter().collect();
+        assert_eq!(values, ["first", "second"]);
+    }
+
+    #[test]
+    fn test_client_builder_rejects_invalid_header() {
+        let result = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL)
+            .with_header("Invalid Header", "value")
+            .build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+
+        let result = ChatGPTClient::builder("dummy_api_key", DEFAULT_BASE_URL)
+            .with_header("X-Valid", "line\nbreak")
+            .build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_message_content_serialization() {
+        let json = serde_json::to_value(Message::new(Role::User, "Hello")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"role": "user", "content": "Hello"})
+        );
+
+        let image = ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+                detail: Some(ImageDetail::Low),
+            },
+        };
+        let message = Message::with_content(
+            Role::User,
+            vec![ContentPart::text("What is in this image?"), image].into(),
+        );
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "What is in this image?"},
+                    {
+                        "type": "image_url",
+                        "image_url": {"url": "data:image/png;base64,iVBORw0KGgo=", "detail": "low"}
+                    }
+                ]
+            })
+        );
+
+        let deserialized: Message = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.content, message.content);
+        assert_eq!(deserialized.content.as_text(), None);
+        assert_eq!(deserialized.content.to_string(), "What is in this image?");
+    }
+
+    #[test]
+    fn test_chat_input_seed_stop_and_penalties_serialization() {
+        let json = serde_json::to_value(sample_chat_input()).unwrap();
+        for field in ["seed", "stop", "presence_penalty", "frequency_penalty"] {
+            assert!(json.get(field).is_none(), "{field}");
+        }
+
+        let input = ChatInput {
+            seed: Some(42),
+            stop: Some(vec!["END".to_string()]),
+            presence_penalty: Some(0.5),
+            frequency_penalty: Some(-0.5),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+
+        assert_eq!(json["seed"], 42);
+        assert_eq!(json["stop"], serde_json::json!(["END"]));
+        assert_eq!(json["presence_penalty"], 0.5);
+        assert_eq!(json["frequency_penalty"], -0.5);
+    }
+
+    #[test]
+    fn test_chat_response_system_fingerprint() {
+        let response = chat_response();
+        assert_eq!(response.system_fingerprint, None);
+
+        let mut json = chat_response_json();
+        json["system_fingerprint"] = serde_json::json!("fp_44709d6fcb");
+        let response: ChatResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            response.system_fingerprint.as_deref(),
+            Some("fp_44709d6fcb")
+        );
+    }
+
+    #[test]
+    fn test_chat_response_logprobs() {
+        let input = ChatInput {
+            logprobs: Some(true),
+            top_logprobs: Some(2),
+            ..sample_chat_input()
+        };
+        let json = serde_json::to_value(input).unwrap();
+        assert_eq!(json["logprobs"], true);
+        assert_eq!(json["top_logprobs"], 2);
+
+        let mut json = chat_response_json();
+        json["choices"][0]["logprobs"] = serde_json::json!({
+            "content": [{
+                "token": "Hello",
+                "logprob": -0.31725305,
+                "bytes": [72, 101, 108, 108, 111],
+                "top_logprobs": [
+                    {"token": "Hello", "logprob": -0.31725305, "bytes": [72, 101, 108, 108, 111]},
+                    {"token": "Hi", "logprob": -1.3190403, "bytes": [72, 105]}
+                ]
+            }],
+            "refusal": null
+        });
+        let response: ChatResponse = serde_json::from_value(json).unwrap();
+        let content = response.choices[0]
+            .logprobs
+            .as_ref()
+            .and_then(|logprobs| logprobs.content.as_ref())
+            .unwrap();
+
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0].token, "Hello");
+        assert_eq!(content[0].logprob, -0.31725305);
+        assert_eq!(content[0].bytes.as_deref(), Some("Hello".as_bytes()));
+        assert_eq!(content[0].top_logprobs[1].token, "Hi");
+        assert_eq!(content[0].top_logprobs[1].logprob, -1.3190403);
+
+        let response = chat_response();
+        assert_eq!(response.choices[0].logprobs, None);
+    }
+
+    #[test]
+    fn test_chat_response_first_message() {
+        let mut json = chat_response_json();
+        let choice = json["choices"][0].clone();
+
+        json["choices"] = serde_json::json!([]);
+        let response: ChatResponse = serde_json::from_value(json.clone()).unwrap();
+        assert!(response.first_message().is_none());
+
+        json["choices"] = serde_json::json!([choice]);
+        let response: ChatResponse = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(response.first_message().unwrap().content, "Hello there!");
+
+        let mut second_choice = choice.clone();
+        second_choice["message"]["content"] = serde_json::json!("Hi!");
+        json["choices"] = serde_json::json!([choice, second_choice]);
+        let response: ChatResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(response.first_message().unwrap().content, "Hello there!");
+    }
+
+    #[test]
+    fn test_chat_input_builder() {
+        let input = ChatInput::builder(Model::Gpt_4o)
+            .with_message(Message::new(Role::System, "Be brief."))
+            .with_messages(vec![Message::new(Role::User, "Hello")])
+            .with_temperature(0.7)
+            .with_max_tokens(100)
+            .with_user("user-1234")
+            .with_metadata("source", "tests")
+            .with_seed(42)
+            .build()
+            .unwrap();
+
+        assert_eq!(input.model, Model::Gpt_4o);
+        assert_eq!(input.messages.len(), 2);
+        assert_eq!(input.messages[1].content, "Hello");
+        assert_eq!(input.temperature, Some(0.7));
+        assert_eq!(input.max_tokens, Some(100));
+        assert_eq!(input.user.as_deref(), Some("user-1234"));
+        assert_eq!(
+            input.metadata,
+            Some(HashMap::from([("source".to_string(), "tests".to_string())]))
+        );
+        assert_eq!(input.seed, Some(42));
+        assert_eq!(input.top_p, None);
+    }
+
+    #[test]
+    fn test_chat_input_builder_rejects_invalid_input() {
+        let result = ChatInput::builder(Model::Gpt_4o).build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+
+        let result = ChatInput::builder(Model::Gpt_4o)
+            .with_message(Message::new(Role::User, "Hello"))
+            .with_stop(vec![String::new()])
+            .build();
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chat_sends_organization_and_project() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("OpenAI-Organization", "org-123"))
+            .and(header("OpenAI-Project", "proj_456"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_organization("org-123")
+            .with_project("proj_456")
+            .build()
+            .unwrap();
+
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_chat_omits_organization_and_project_by_default() {
+        use wiremock::MockServer;
+
+        let server = MockServer::start().await;
+        mount_chat_success(&server).await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        assert!(client.chat(sample_chat_input()).await.is_ok());
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(!requests[0].headers.contains_key("OpenAI-Organization"));
+        assert!(!requests[0].headers.contains_key("OpenAI-Project"));
+    }
+
+    #[test]
+    fn test_chat_response_estimated_cost() {
+        let mut json = chat_response_json();
+        json["usage"] = serde_json::json!({
+            "prompt_tokens": 2000, "completion_tokens": 1000, "total_tokens": 3000
+        });
+
+        for (model, expected) in [
+            ("gpt-4o", Some(0.015)),
+            ("gpt-4o-2024-08-06", Some(0.015)),
+            ("gpt-4", Some(0.12)),
+            ("text-davinci-003", None),
+        ] {
+            json["model"] = serde_json::json!(model);
+            let response: ChatResponse = serde_json::from_value(json.clone()).unwrap();
+            let cost = response.estimated_cost();
+
+            match (cost, expected) {
+                (Some(cost), Some(expected)) => assert!((cost - expected).abs() < 1e-9, "{model}"),
+                _ => assert_eq!(cost, expected, "{model}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_snapshot_date() {
+        assert_eq!(strip_snapshot_date("gpt-4o-2024-08-06"), "gpt-4o");
+        assert_eq!(strip_snapshot_date("gpt-4o"), "gpt-4o");
+        assert_eq!(
+            strip_snapshot_date("gpt-4-1106-preview"),
+            "gpt-4-1106-preview"
+        );
+        assert_eq!(strip_snapshot_date(""), "");
+        assert_eq!(
+            strip_snapshot_date("modèle-2024-08-0é"),
+            "modèle-2024-08-0é"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_key() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("Authorization", "Bearer tenant-key"))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("default-key", &server.uri());
+        let response = client
+            .chat_with_key(sample_chat_input(), "tenant-key")
+            .await
+            .unwrap();
+        assert_eq!(response.id, "chatcmpl-123");
+
+        let result = client.chat(sample_chat_input()).await;
+        assert!(matches!(result, Err(ChatGPTError::AuthError { .. })));
+    }
+
+    #[test]
+    fn test_client_builder_build() {
+        let client = ChatGPTClient::builder("dummy_api_key", "https://dummy-api-url.com")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_key, "dummy_api_key");
+        assert_eq!(client.base_url, "https://dummy-api-url.com");
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_chat_with_compression_decodes_gzip() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&serde_json::to_vec(&chat_response_json()).unwrap())
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .insert_header("Content-Type", "application/json")
+                    .set_body_bytes(compressed),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::builder("dummy_api_key", &server.uri())
+            .with_compression(true)
+            .build()
+            .unwrap();
+
+        let response = client.chat(sample_chat_input()).await.unwrap();
+        assert_eq!(response.id, "chatcmpl-123");
+        assert_eq!(response.choices[0].message.content, "Hello there!");
     }
 }
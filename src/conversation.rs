@@ -0,0 +1,249 @@
+use crate::client::{ChatGPTError, ChatInput, ChatResponse, Message};
+use crate::models::{Model, Role};
+use crate::tokenizer::{count_tokens, truncate_messages};
+
+/// Holds the history of a multi-turn chat conversation.
+///
+/// A `Conversation` keeps a system prompt together with the user and assistant
+/// turns, so a chat loop only needs to push the user input, send the request
+/// and ingest the response. With a context limit set, the oldest turns are
+/// left out of the request when the estimated token count exceeds the limit.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::{ChatGPTClient, Model};
+/// use chat_gpt_lib_rs::conversation::Conversation;
+///
+/// async fn example() {
+///     let client = ChatGPTClient::new("your_api_key", "https://api.openai.com");
+///     let mut conversation =
+///         Conversation::new("You are a helpful assistant.").with_max_context_tokens(4096);
+///
+///     conversation.push_user("Who is the best field hockey player in the world?");
+///     let input = conversation.to_request(Model::Gpt_4o).unwrap();
+///     let response = client.chat(input).await.unwrap();
+///     conversation.ingest_response(&response);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    system_prompt: String,
+    history: Vec<Message>,
+    max_context_tokens: Option<usize>,
+}
+
+impl Conversation {
+    /// Creates a new, empty conversation with the given system prompt.
+    pub fn new(system_prompt: &str) -> Self {
+        Self {
+            system_prompt: system_prompt.to_string(),
+            history: Vec::new(),
+            max_context_tokens: None,
+        }
+    }
+
+    /// Limits the estimated number of tokens sent by [`Conversation::to_request`].
+    ///
+    /// The oldest user and assistant turns are dropped from the request until it
+    /// fits; the system prompt is always kept.
+    pub fn with_max_context_tokens(mut self, max_context_tokens: usize) -> Self {
+        self.max_context_tokens = Some(max_context_tokens);
+        self
+    }
+
+    /// Appends a user message to the conversation.
+    pub fn push_user(&mut self, content: &str) {
+        self.push(Role::User, content);
+    }
+
+    /// Appends an assistant message to the conversation.
+    pub fn push_assistant(&mut self, content: &str) {
+        self.push(Role::Assistant, content);
+    }
+
+    fn push(&mut self, role: Role, content: &str) {
+        self.history.push(Message::new(role, content));
+    }
+
+    /// Returns the user and assistant turns, without the system prompt.
+    pub fn history(&self) -> &[Message] {
+        &self.history
+    }
+
+    /// Returns all messages of the conversation, starting with the system prompt.
+    pub fn messages(&self) -> Vec<Message> {
+        let mut messages = Vec::with_capacity(self.history.len() + 1);
+        messages.push(Message::new(Role::System, &self.system_prompt));
+        messages.extend(self.history.iter().cloned());
+        messages
+    }
+
+    /// Builds a ChatInput for the given model containing the conversation.
+    ///
+    /// When a context limit is set, the oldest turns that do not fit are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError::ConfigError if the system prompt alone exceeds the context limit,
+    /// or if the most recent turn does not fit next to the system prompt.
+    pub fn to_request(&self, model: Model) -> Result<ChatInput, ChatGPTError> {
+        let mut messages = self.messages();
+
+        if let Some(max_tokens) = self.max_context_tokens {
+            if count_tokens(&self.system_prompt) > max_tokens {
+                return Err(ChatGPTError::ConfigError(format!(
+                    "System prompt exceeds the context limit of {max_tokens} tokens"
+                )));
+            }
+            truncate_messages(&mut messages, max_tokens);
+            // Only the system prompt is left, so the most recent turn was dropped as well
+            if messages.len() == 1 && !self.history.is_empty() {
+                return Err(ChatGPTError::ConfigError(format!(
+                    "The most recent message exceeds the context limit of {max_tokens} tokens"
+                )));
+            }
+        }
+
+        Ok(ChatInput {
+            model,
+            messages,
+            ..Default::default()
+        })
+    }
+
+    /// Appends the assistant's reply from the first choice of the response.
+    ///
+    /// Responses without choices leave the conversation unchanged.
+    pub fn ingest_response(&mut self, response: &ChatResponse) {
+        if let Some(choice) = response.choices.first() {
+            self.history.push(choice.message.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Annotation;
+    use crate::test_utils::chat_response_with_reply;
+
+    #[test]
+    fn test_conversation_turns() {
+        let mut conversation = Conversation::new("You are a helpful assistant.");
+        conversation.push_user("Hi");
+        conversation.ingest_response(&chat_response_with_reply("Hello!"));
+        conversation.push_user("How are you?");
+
+        let messages = conversation.messages();
+        let roles: Vec<Role> = messages.iter().map(|m| m.role.clone()).collect();
+        let contents: Vec<&str> = messages
+            .iter()
+            .map(|m| m.content.as_text().unwrap())
+            .collect();
+
+        assert_eq!(
+            roles,
+            vec![Role::System, Role::User, Role::Assistant, Role::User]
+        );
+        assert_eq!(
+            contents,
+            vec![
+                "You are a helpful assistant.",
+                "Hi",
+                "Hello!",
+                "How are you?"
+            ]
+        );
+        assert_eq!(conversation.history().len(), 3);
+    }
+
+    #[test]
+    fn test_conversation_to_request() {
+        let mut conversation = Conversation::new("Be brief.");
+        conversation.push_user("Hi");
+        conversation.push_assistant("Hello!");
+
+        let input = conversation.to_request(Model::Gpt_4o).unwrap();
+        assert_eq!(input.model, Model::Gpt_4o);
+        assert_eq!(input.messages.len(), 3);
+        assert_eq!(input.messages[0].role, Role::System);
+        assert_eq!(input.messages[2].content, "Hello!");
+    }
+
+    #[test]
+    fn test_conversation_truncates_oldest_turns() {
+        let mut conversation = Conversation::new("System prompt").with_max_context_tokens(12);
+        conversation.push_user("first question");
+        conversation.push_assistant("first answer");
+        conversation.push_user("second question");
+        conversation.push_assistant("second answer");
+        conversation.push_user("third question");
+
+        let input = conversation.to_request(Model::Gpt_4o).unwrap();
+        let contents: Vec<&str> = input
+            .messages
+            .iter()
+            .map(|m| m.content.as_text().unwrap())
+            .collect();
+
+        assert_eq!(
+            contents,
+            vec![
+                "System prompt",
+                "second question",
+                "second answer",
+                "third question"
+            ]
+        );
+        assert_eq!(conversation.history().len(), 5);
+    }
+
+    #[test]
+    fn test_conversation_system_prompt_exceeds_limit() {
+        let conversation =
+            Conversation::new("A system prompt that is far too long").with_max_context_tokens(2);
+
+        let result = conversation.to_request(Model::Gpt_4o);
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_conversation_most_recent_turn_exceeds_limit() {
+        let mut conversation = Conversation::new("System prompt").with_max_context_tokens(12);
+        conversation.push_user("short question");
+        conversation.push_assistant("short answer");
+        conversation.push_user("a very long question that does not fit in the context");
+
+        let result = conversation.to_request(Model::Gpt_4o);
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+
+        let conversation = Conversation::new("System prompt").with_max_context_tokens(12);
+        assert_eq!(
+            conversation
+                .to_request(Model::Gpt_4o)
+                .unwrap()
+                .messages
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_conversation_does_not_send_annotations() {
+        let mut response = chat_response_with_reply("See the rules of field hockey.");
+        response.choices[0].message.annotations = Some(vec![Annotation::Other]);
+
+        let mut conversation = Conversation::new("Be brief.");
+        conversation.push_user("What are the rules of field hockey?");
+        conversation.ingest_response(&response);
+        conversation.push_user("Thanks!");
+
+        let input = conversation.to_request(Model::Gpt_4o).unwrap();
+        let json = serde_json::to_value(&input).unwrap();
+        let messages = json["messages"].as_array().unwrap();
+
+        assert_eq!(messages.len(), 4);
+        assert!(messages.iter().all(|m| m.get("annotations").is_none()));
+    }
+}
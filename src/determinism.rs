@@ -0,0 +1,97 @@
+use crate::client::ChatResponse;
+use std::collections::HashMap;
+
+/// Represents a change of the backend configuration between two responses.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FingerprintChange {
+    pub previous: String,
+    pub current: String,
+}
+
+/// Detects backend changes that may break determinism of seeded requests.
+///
+/// Requests with the same `seed` and parameters are mostly deterministic as long as the
+/// `system_fingerprint` of the responses stays the same. The monitor remembers the last
+/// fingerprint seen for each model and seed, and reports when it changes.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::determinism::DeterminismMonitor;
+/// use chat_gpt_lib_rs::{ChatGPTClient, ChatInput};
+///
+/// async fn example(client: &ChatGPTClient, input: ChatInput) {
+///     let mut monitor = DeterminismMonitor::new();
+///     let response = client.chat(input).await.unwrap();
+///     if let Some(change) = monitor.observe(42, &response) {
+///         println!("Backend changed from {} to {}", change.previous, change.current);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct DeterminismMonitor {
+    fingerprints: HashMap<(String, i64), String>,
+}
+
+impl DeterminismMonitor {
+    /// Creates a new monitor without any recorded fingerprints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the fingerprint of a response to a request with the given seed.
+    ///
+    /// Responses without a `system_fingerprint` are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * The previous and current fingerprint if the fingerprint changed since the last
+    ///   response for the same model and seed, or `None` otherwise.
+    pub fn observe(&mut self, seed: i64, response: &ChatResponse) -> Option<FingerprintChange> {
+        let current = response.system_fingerprint.clone()?;
+        let previous = self
+            .fingerprints
+            .insert((response.model.clone(), seed), current.clone())?;
+
+        (previous != current).then_some(FingerprintChange { previous, current })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::chat_response_json;
+
+    fn response(model: &str, fingerprint: Option<&str>) -> ChatResponse {
+        let mut json = chat_response_json();
+        json["model"] = serde_json::json!(model);
+        json["system_fingerprint"] = serde_json::json!(fingerprint);
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_observe_detects_fingerprint_change() {
+        let mut monitor = DeterminismMonitor::new();
+
+        assert_eq!(monitor.observe(42, &response("gpt-4o", Some("fp_1"))), None);
+        assert_eq!(monitor.observe(42, &response("gpt-4o", Some("fp_1"))), None);
+        assert_eq!(
+            monitor.observe(42, &response("gpt-4o", Some("fp_2"))),
+            Some(FingerprintChange {
+                previous: "fp_1".to_string(),
+                current: "fp_2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_observe_tracks_model_and_seed_separately() {
+        let mut monitor = DeterminismMonitor::new();
+
+        assert_eq!(monitor.observe(1, &response("gpt-4o", Some("fp_1"))), None);
+        assert_eq!(monitor.observe(2, &response("gpt-4o", Some("fp_2"))), None);
+        assert_eq!(monitor.observe(1, &response("gpt-4", Some("fp_3"))), None);
+        assert_eq!(monitor.observe(1, &response("gpt-4o", None)), None);
+        assert_eq!(monitor.observe(1, &response("gpt-4o", Some("fp_1"))), None);
+    }
+}
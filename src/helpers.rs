@@ -0,0 +1,104 @@
+use crate::client::{ChatGPTClient, ChatGPTError, ChatInput, Message};
+use crate::models::{Model, Role};
+
+impl ChatGPTClient {
+    /// Sends a single user prompt, optionally preceded by a system prompt, and returns
+    /// the text of the assistant's reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model to use.
+    /// * `system_prompt` - The instructions for the assistant, if any.
+    /// * `user_prompt` - The question or input of the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chat_gpt_lib_rs::{ChatGPTClient, Model};
+    ///
+    /// async fn example() {
+    ///     let chat_gpt = ChatGPTClient::new("your_api_key", "https://api.openai.com");
+    ///     let reply = chat_gpt
+    ///         .simple_chat(Model::Gpt_4o, Some("Be brief."), "What is field hockey?")
+    ///         .await
+    ///         .unwrap();
+    ///     println!("{reply}");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails, or a ChatGPTError::UnexpectedResponse
+    /// if the response contains no choices.
+    pub async fn simple_chat(
+        &self,
+        model: Model,
+        system_prompt: Option<&str>,
+        user_prompt: &str,
+    ) -> Result<String, ChatGPTError> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(Message::new(Role::System, system_prompt));
+        }
+        messages.push(Message::new(Role::User, user_prompt));
+
+        let input = ChatInput {
+            model,
+            messages,
+            ..Default::default()
+        };
+        let message = self.chat_message(input).await?;
+        Ok(message.content.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{chat_response_with_choices, chat_success};
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_simple_chat() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "gpt-4o",
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hello"}
+                ]
+            })))
+            .respond_with(chat_success())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let reply = client
+            .simple_chat(Model::Gpt_4o, Some("Be brief."), "Hello")
+            .await
+            .unwrap();
+
+        assert_eq!(reply, "Hello there!");
+    }
+
+    #[tokio::test]
+    async fn test_simple_chat_without_choices() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(chat_response_with_choices(serde_json::json!([]))),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let result = client.simple_chat(Model::Gpt_4o, None, "Hello").await;
+
+        assert!(matches!(result, Err(ChatGPTError::UnexpectedResponse(_))));
+    }
+}
@@ -5,19 +5,55 @@
 //! This crate exports the following main items:
 //!
 //! - [`ChatGPTClient`]: Represents the main client to interact with the ChatGPT API.
+//! - [`ClientBuilder`]: Configures a [`ChatGPTClient`] with non-default settings.
+//! - [`ChatGPTError`]: Represents the errors returned by the [`ChatGPTClient`].
+//! - [`ErrorCategory`]: Broad classification of a [`ChatGPTError`] for retry decisions.
 //! - [`ChatInput`]: Represents the input for the chat API call.
+//! - [`ChatInputBuilder`]: Constructs a [`ChatInput`] fluently.
+//! - [`Conversation`]: Holds the message history of a multi-turn chat conversation.
+//! - [`ChatResponseFormat`]: Represents the format the model must output, such as JSON.
 //! - [`ChatResponse`]: Represents the response from the chat API call.
 //! - [`Message`]: Represents a message in the chat API call.
+//! - [`MessageContent`]: Represents the content of a message, text or parts such as images.
 //! - [`Model`]: Represents the available OpenAI models.
+//! - [`ModelFamily`]: Represents the family of a [`Model`], such as GPT-4.
+//! - [`ServiceTier`]: Represents the processing tier used for serving a request.
 //! - [`Role`]: Represents the role of a message in the chat API call.
+//! - [`FinishReason`]: Represents the reason the model stopped generating tokens.
+//! - [`Tool`]: Represents a tool, such as a function, the model may call.
 //! - [`LogitBias`]: Represents the logit bias used in API calls.
+//! - [`RateLimitInfo`]: Represents the rate limit information sent in the response headers.
+//! - [`build_batch_input`]: Builds the JSONL contents of a batch input file from chat inputs.
+//! - [`estimate_cost`]: Provides a rough estimation of the cost in USD of a request.
+//! - [`render`]: Renders a prompt template with `{{name}}` placeholders.
 //! - [`count_tokens`]: Provides a rough estimation of the number of tokens in a given text.
 //! For examples and more detailed usage information, please refer to the documentation of each exported item.
 
+pub mod batch;
 pub mod client;
+pub mod conversation;
+pub mod determinism;
+pub mod helpers;
 pub mod models;
+pub mod pricing;
+pub mod rate_limit;
+#[cfg(feature = "structured-outputs")]
+pub mod structured_outputs;
+pub mod templates;
+#[cfg(test)]
+mod test_utils;
 pub mod tokenizer;
+pub mod tools;
 
-pub use client::{ChatGPTClient, ChatInput, ChatResponse, Message};
-pub use models::{LogitBias, Model, Role};
+pub use batch::build_batch_input;
+pub use client::{
+    ChatGPTClient, ChatGPTError, ChatInput, ChatInputBuilder, ChatResponse, ChatResponseFormat,
+    ClientBuilder, ErrorCategory, Message, MessageContent,
+};
+pub use conversation::Conversation;
+pub use models::{FinishReason, LogitBias, Model, ModelFamily, Role, ServiceTier};
+pub use pricing::estimate_cost;
+pub use rate_limit::RateLimitInfo;
+pub use templates::render;
 pub use tokenizer::count_tokens;
+pub use tools::{Tool, ToolCall, ToolChoice};
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::fmt::Result as FmtResult;
 use std::fmt::{Display, Formatter};
@@ -11,7 +11,7 @@ use thiserror::Error;
 /// Currently supported models are:
 /// - Gpt3_5Turbo
 /// - Gpt4
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[allow(non_camel_case_types)] // Add this line to suppress the warning
 pub enum Model {
@@ -27,6 +27,10 @@ pub enum Model {
     Gpt_4o,
     #[serde(rename = "gpt-4-vision-preview")]
     Gpt_4Turbo_Vision,
+    #[serde(rename = "gpt-4o-search-preview")]
+    Gpt_4oSearchPreview,
+    #[serde(rename = "gpt-4o-mini-search-preview")]
+    Gpt_4oMiniSearchPreview,
 }
 
 impl Model {
@@ -38,10 +42,56 @@ impl Model {
             Model::Gpt_4o => 128000,
             Model::Gpt_4Turbo => 128000,
             Model::Gpt_4Turbo_Vision => 128000,
+            Model::Gpt_4oSearchPreview => 128000,
+            Model::Gpt_4oMiniSearchPreview => 128000,
+        }
+    }
+
+    /// Returns the maximum number of tokens of the context window, the prompt and
+    /// completion combined, of the current version of the model.
+    ///
+    /// Use this to check a prompt fits before sending a request.
+    pub fn context_window(&self) -> u32 {
+        match self {
+            Model::Gpt3_5Turbo => 16385,
+            Model::Gpt_4 => 8192,
+            Model::Gpt_4_32k => 32768,
+            Model::Gpt_4o => 128000,
+            Model::Gpt_4Turbo => 128000,
+            Model::Gpt_4Turbo_Vision => 128000,
+            Model::Gpt_4oSearchPreview => 128000,
+            Model::Gpt_4oMiniSearchPreview => 128000,
+        }
+    }
+
+    /// Returns the family the model belongs to.
+    pub fn family(&self) -> ModelFamily {
+        match self {
+            Model::Gpt3_5Turbo => ModelFamily::Gpt35,
+            Model::Gpt_4
+            | Model::Gpt_4_32k
+            | Model::Gpt_4o
+            | Model::Gpt_4Turbo
+            | Model::Gpt_4Turbo_Vision
+            | Model::Gpt_4oSearchPreview
+            | Model::Gpt_4oMiniSearchPreview => ModelFamily::Gpt4,
         }
     }
 }
 
+/// `ModelFamily` enum represents the family of a [`Model`].
+///
+/// The `ModelFamily` enum currently has the following variants, more families may be
+/// added as new models are supported:
+/// - `Gpt35`: The GPT-3.5 models.
+/// - `Gpt4`: The GPT-4 models, including GPT-4 Turbo and GPT-4o.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum ModelFamily {
+    Gpt35,
+    Gpt4,
+}
+
 /// Implement Display to convert the enum back to a string representation.
 impl Display for Model {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
@@ -52,6 +102,8 @@ impl Display for Model {
             Model::Gpt_4o => "gpt-4o",
             Model::Gpt_4Turbo => "gpt-4-1106-preview",
             Model::Gpt_4Turbo_Vision => "gpt-4-vision-preview",
+            Model::Gpt_4oSearchPreview => "gpt-4o-search-preview",
+            Model::Gpt_4oMiniSearchPreview => "gpt-4o-mini-search-preview",
         };
         write!(f, "{model_name}")
     }
@@ -69,6 +121,8 @@ impl FromStr for Model {
             "gpt-4o" => Ok(Model::Gpt_4o),
             "gpt-4-1106-preview" => Ok(Model::Gpt_4Turbo),
             "gpt-4-vision-preview" => Ok(Model::Gpt_4Turbo_Vision),
+            "gpt-4o-search-preview" => Ok(Model::Gpt_4oSearchPreview),
+            "gpt-4o-mini-search-preview" => Ok(Model::Gpt_4oMiniSearchPreview),
             _ => Err(ModelError::UnsupportedModel(s.into())),
         }
     }
@@ -92,10 +146,11 @@ pub struct LogitBias {
 
 /// Represents the role of a message in the Chat API call.
 ///
-/// The `Role` enum has three variants:
+/// The `Role` enum has four variants:
 /// - `System`: Represents a system message, usually to provide instructions to the assistant.
 /// - `User`: Represents a user message, which is the input or question the user provides.
 /// - `Assistant`: Represents an assistant message, which is the response generated by the Chat API.
+/// - `Tool`: Represents a tool message, which holds the result of a tool call.
 ///
 /// The role is used to differentiate between different types of messages in the chat conversation.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -104,6 +159,97 @@ pub enum Role {
     System,
     User,
     Assistant,
+    Tool,
+}
+
+/// Represents the processing tier used for serving a request.
+///
+/// The `ServiceTier` enum has the following variants:
+/// - `Auto`: Let the project settings decide which tier to use.
+/// - `Default`: Use the standard tier.
+/// - `Flex`: Use the flex tier, trading latency for lower cost.
+/// - `Scale`: Use the scale tier, only returned for projects with scale tier credits.
+/// - `Priority`: Use the priority tier.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceTier {
+    Auto,
+    Default,
+    Flex,
+    Scale,
+    Priority,
+}
+
+/// Represents the reason the model stopped generating tokens for a choice.
+///
+/// The `FinishReason` enum has the following variants:
+/// - `Stop`: The model hit a natural stop point or a provided stop sequence.
+/// - `Length`: The maximum number of tokens was reached.
+/// - `ContentFilter`: Content was omitted due to a content filter flag.
+/// - `ToolCalls`: The model called a tool.
+/// - `FunctionCall`: The model called a function, with the deprecated `functions` parameter.
+/// - `Other`: Any reason not known to this crate, holding the raw string.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ContentFilter,
+    ToolCalls,
+    FunctionCall,
+    Other(String),
+}
+
+impl FinishReason {
+    /// Returns the string representation used by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::Length => "length",
+            FinishReason::ContentFilter => "content_filter",
+            FinishReason::ToolCalls => "tool_calls",
+            FinishReason::FunctionCall => "function_call",
+            FinishReason::Other(reason) => reason,
+        }
+    }
+
+    /// Returns true if the output was cut off because the maximum number of tokens was reached.
+    ///
+    /// Unlike [`crate::ChatResponse::was_truncated`], content filtering is not considered.
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, FinishReason::Length)
+    }
+}
+
+impl From<&str> for FinishReason {
+    fn from(s: &str) -> Self {
+        match s {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::Length,
+            "content_filter" => FinishReason::ContentFilter,
+            "tool_calls" => FinishReason::ToolCalls,
+            "function_call" => FinishReason::FunctionCall,
+            other => FinishReason::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for FinishReason {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for FinishReason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FinishReason {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let reason = String::deserialize(deserializer)?;
+        Ok(FinishReason::from(reason.as_str()))
+    }
 }
 
 #[cfg(test)]
@@ -388,4 +534,113 @@ mod tests {
         let model = Model::Gpt_4o;
         assert_eq!(model.max_tokens(), 128000);
     }
+
+    // Test the deserialization of the known finish reasons.
+    #[test]
+    fn test_deserialize_finish_reason_known() {
+        let cases = [
+            ("\"stop\"", FinishReason::Stop),
+            ("\"length\"", FinishReason::Length),
+            ("\"content_filter\"", FinishReason::ContentFilter),
+            ("\"tool_calls\"", FinishReason::ToolCalls),
+            ("\"function_call\"", FinishReason::FunctionCall),
+        ];
+        for (json, expected) in cases {
+            let reason: FinishReason = serde_json::from_str(json).unwrap();
+            assert_eq!(reason, expected);
+        }
+    }
+
+    // Test that an unknown finish reason is kept in the `Other` variant.
+    #[test]
+    fn test_deserialize_finish_reason_other() {
+        let reason: FinishReason = serde_json::from_str("\"something_new\"").unwrap();
+        assert_eq!(reason, FinishReason::Other("something_new".to_string()));
+    }
+
+    // Test the serialization of a `FinishReason` back to its string representation.
+    #[test]
+    fn test_serialize_finish_reason() {
+        let serialized = serde_json::to_string(&FinishReason::ContentFilter).unwrap();
+        assert_eq!(serialized, "\"content_filter\"");
+        let serialized = serde_json::to_string(&FinishReason::Other("x".to_string())).unwrap();
+        assert_eq!(serialized, "\"x\"");
+    }
+
+    // Test the serialization and deserialization of the `ServiceTier` variants.
+    #[test]
+    fn test_service_tier_serde() {
+        assert_eq!(
+            serde_json::to_string(&ServiceTier::Auto).unwrap(),
+            "\"auto\""
+        );
+        assert_eq!(
+            serde_json::to_string(&ServiceTier::Flex).unwrap(),
+            "\"flex\""
+        );
+        let tier: ServiceTier = serde_json::from_str("\"default\"").unwrap();
+        assert_eq!(tier, ServiceTier::Default);
+    }
+
+    // Test that `Model` can be used as a `HashMap` key.
+    #[test]
+    fn test_model_as_hash_map_key() {
+        let mut limits = HashMap::new();
+        limits.insert(Model::Gpt3_5Turbo, 10);
+        limits.insert(Model::Gpt_4, 20);
+        limits.insert(Model::Gpt_4o, 30);
+
+        assert_eq!(limits.get(&Model::Gpt3_5Turbo), Some(&10));
+        assert_eq!(limits.get(&Model::Gpt_4), Some(&20));
+        assert_eq!(limits.get(&Model::Gpt_4o), Some(&30));
+        assert_eq!(limits.get(&Model::Gpt_4_32k), None);
+    }
+
+    // Test the conversion of the search-preview models to and from their string representation.
+    #[test]
+    fn test_display_and_from_str_search_preview() {
+        for (model, name) in [
+            (Model::Gpt_4oSearchPreview, "gpt-4o-search-preview"),
+            (Model::Gpt_4oMiniSearchPreview, "gpt-4o-mini-search-preview"),
+        ] {
+            assert_eq!(model.to_string(), name);
+            assert_eq!(Model::from_str(name).unwrap(), model);
+            assert_eq!(
+                serde_json::to_string(&model).unwrap(),
+                format!("\"{name}\"")
+            );
+            assert_eq!(model.max_tokens(), 128000);
+        }
+    }
+
+    #[test]
+    fn test_context_window_and_family() {
+        let models = [
+            (Model::Gpt3_5Turbo, 16385, ModelFamily::Gpt35),
+            (Model::Gpt_4, 8192, ModelFamily::Gpt4),
+            (Model::Gpt_4_32k, 32768, ModelFamily::Gpt4),
+            (Model::Gpt_4Turbo, 128000, ModelFamily::Gpt4),
+            (Model::Gpt_4o, 128000, ModelFamily::Gpt4),
+            (Model::Gpt_4Turbo_Vision, 128000, ModelFamily::Gpt4),
+            (Model::Gpt_4oSearchPreview, 128000, ModelFamily::Gpt4),
+            (Model::Gpt_4oMiniSearchPreview, 128000, ModelFamily::Gpt4),
+        ];
+
+        for (model, context_window, family) in models {
+            assert_eq!(model.context_window(), context_window, "{model}");
+            assert_eq!(model.family(), family, "{model}");
+            assert!(model.context_window() as usize >= model.max_tokens());
+        }
+    }
+
+    // Test that only the `Length` finish reason counts as truncated.
+    #[test]
+    fn test_finish_reason_is_truncated() {
+        assert!(FinishReason::Length.is_truncated());
+        assert!(!FinishReason::Stop.is_truncated());
+        assert!(!FinishReason::ContentFilter.is_truncated());
+        assert!(!FinishReason::ToolCalls.is_truncated());
+        assert!(!FinishReason::FunctionCall.is_truncated());
+        assert!(!FinishReason::Other("length_exceeded".to_string()).is_truncated());
+    }
 }
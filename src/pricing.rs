@@ -0,0 +1,70 @@
+use crate::client::Usage;
+use crate::models::Model;
+
+/// Returns the price in USD per 1K tokens as `(input, output)` for the given model.
+///
+/// Returns `None` if no price is known for the model.
+pub fn price_per_1k_tokens(model: &Model) -> Option<(f64, f64)> {
+    match model {
+        Model::Gpt3_5Turbo => Some((0.0005, 0.0015)),
+        Model::Gpt_4 => Some((0.03, 0.06)),
+        Model::Gpt_4_32k => Some((0.06, 0.12)),
+        Model::Gpt_4Turbo => Some((0.01, 0.03)),
+        Model::Gpt_4o => Some((0.0025, 0.01)),
+        Model::Gpt_4Turbo_Vision => Some((0.01, 0.03)),
+        Model::Gpt_4oSearchPreview => Some((0.0025, 0.01)),
+        Model::Gpt_4oMiniSearchPreview => Some((0.00015, 0.0006)),
+    }
+}
+
+/// Estimates the cost in USD of a request based on its token usage.
+///
+/// The estimate uses a built-in price table, which may lag behind OpenAI's
+/// current pricing. Prompt and completion tokens are priced separately.
+///
+/// # Arguments
+///
+/// * `model` - The model that served the request.
+/// * `usage` - The token usage reported in the response.
+///
+/// # Returns
+///
+/// * The estimated cost in USD, or `None` if no price is known for `model`.
+pub fn estimate_cost(model: &Model, usage: &Usage) -> Option<f64> {
+    let (input_price, output_price) = price_per_1k_tokens(model)?;
+    let input_cost = usage.prompt_tokens as f64 / 1000.0 * input_price;
+    let output_cost = usage.completion_tokens as f64 / 1000.0 * output_price;
+    Some(input_cost + output_cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt_tokens: i64, completion_tokens: i64) -> Usage {
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost_gpt_4() {
+        let cost = estimate_cost(&Model::Gpt_4, &usage(1000, 500)).unwrap();
+        assert!((cost - 0.06).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_cost_gpt_4o() {
+        let cost = estimate_cost(&Model::Gpt_4o, &usage(2000, 1000)).unwrap();
+        assert!((cost - 0.015).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_cost_zero_usage() {
+        let cost = estimate_cost(&Model::Gpt3_5Turbo, &usage(0, 0)).unwrap();
+        assert_eq!(cost, 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// Represents the rate limit information sent by the API in the response headers.
+///
+/// Each field is `None` when the corresponding `x-ratelimit-*` header is absent
+/// or cannot be parsed.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub reset_requests: Option<Duration>,
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Parses the `x-ratelimit-*` headers into a RateLimitInfo.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let number = |name: &str| header_str(headers, name).and_then(|v| v.parse().ok());
+        let duration = |name: &str| header_str(headers, name).and_then(parse_duration);
+
+        Self {
+            limit_requests: number("x-ratelimit-limit-requests"),
+            limit_tokens: number("x-ratelimit-limit-tokens"),
+            remaining_requests: number("x-ratelimit-remaining-requests"),
+            remaining_tokens: number("x-ratelimit-remaining-tokens"),
+            reset_requests: duration("x-ratelimit-reset-requests"),
+            reset_tokens: duration("x-ratelimit-reset-tokens"),
+        }
+    }
+}
+
+/// Parses the time to wait before retrying from the `retry-after-ms` or `retry-after` header.
+///
+/// Only the delay-seconds form of `retry-after` is supported, HTTP dates are ignored.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(millis) = header_str(headers, "retry-after-ms").and_then(|v| v.parse().ok()) {
+        return Some(Duration::from_millis(millis));
+    }
+    header_str(headers, "retry-after")
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Parses a duration in the format used by the rate limit headers, such as `1s`, `6m0s` or `20ms`.
+///
+/// Returns `None` if the value is empty or contains an unknown unit.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut nanos = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let number: f64 = rest[..number_end].parse().ok()?;
+
+        let unit_rest = &rest[number_end..];
+        let unit_end = unit_rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(unit_rest.len());
+        let unit_nanos = match &unit_rest[..unit_end] {
+            "h" => 3_600_000_000_000.0,
+            "m" => 60_000_000_000.0,
+            "s" => 1_000_000_000.0,
+            "ms" => 1_000_000.0,
+            _ => return None,
+        };
+
+        nanos += number * unit_nanos;
+        rest = &unit_rest[unit_end..];
+    }
+
+    Some(Duration::from_nanos(nanos.round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_duration("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_duration("1h2m3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+
+    #[test]
+    fn test_rate_limit_info_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit-requests", "60".parse().unwrap());
+        headers.insert("x-ratelimit-limit-tokens", "150000".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-requests", "59".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "149984".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "1s".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "6m0s".parse().unwrap());
+
+        let info = RateLimitInfo::from_headers(&headers);
+
+        assert_eq!(
+            info,
+            RateLimitInfo {
+                limit_requests: Some(60),
+                limit_tokens: Some(150000),
+                remaining_requests: Some(59),
+                remaining_tokens: Some(149984),
+                reset_requests: Some(Duration::from_secs(1)),
+                reset_tokens: Some(Duration::from_secs(360)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_info_missing_headers() {
+        let info = RateLimitInfo::from_headers(&HeaderMap::new());
+        assert_eq!(info, RateLimitInfo::default());
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert("retry-after", "5".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+
+        headers.insert("retry-after-ms", "250".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(250)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "retry-after",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_retry_after_out_of_range() {
+        for value in ["1e20", "-5", "NaN", "inf"] {
+            let mut headers = HeaderMap::new();
+            headers.insert("retry-after", value.parse().unwrap());
+            assert_eq!(retry_after(&headers), None, "{value}");
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::client::{ChatGPTClient, ChatGPTError, ChatInput, ChatResponseFormat, JsonSchemaFormat};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+impl ChatGPTClient {
+    /// Sends a request with a strict JSON schema generated from `T` as response format,
+    /// and deserializes the content of the first choice into `T`.
+    ///
+    /// Any `response_format` of the input is replaced. Only available with the
+    /// `structured-outputs` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A ChatInput struct representing the input for the chat API call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chat_gpt_lib_rs::{ChatGPTClient, ChatInput, Message, Model, Role};
+    /// use schemars::JsonSchema;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct Player {
+    ///     name: String,
+    ///     country: String,
+    /// }
+    ///
+    /// async fn example() {
+    ///     let chat_gpt = ChatGPTClient::new("your_api_key", "https://api.openai.com");
+    ///     let input = ChatInput {
+    ///         model: Model::Gpt_4o,
+    ///         messages: vec![Message::new(
+    ///             Role::User,
+    ///             "Who is the best field hockey player in the world",
+    ///         )],
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let player: Player = chat_gpt.chat_parsed(input).await.unwrap();
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a ChatGPTError if the request fails, or a ChatGPTError::UnexpectedResponse if
+    /// the response contains no choices or the content cannot be deserialized into `T`.
+    pub async fn chat_parsed<T>(&self, input: ChatInput) -> Result<T, ChatGPTError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        let input = ChatInput {
+            response_format: Some(response_format_for::<T>()),
+            ..input
+        };
+        let content = self.chat_message(input).await?.content.to_string();
+        serde_json::from_str(&content).map_err(|err| {
+            ChatGPTError::UnexpectedResponse(format!(
+                "Response content does not match the schema ({err}): {content}"
+            ))
+        })
+    }
+}
+
+/// Generates a strict JSON schema response format for `T`.
+fn response_format_for<T: JsonSchema>() -> ChatResponseFormat {
+    let generator = SchemaSettings::draft2019_09()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator();
+    let mut schema = serde_json::to_value(generator.into_root_schema_for::<T>())
+        .expect("A JSON schema is always serializable");
+    if let Value::Object(root) = &mut schema {
+        root.remove("$schema");
+    }
+    make_strict(&mut schema);
+
+    let name = T::schema_name()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    ChatResponseFormat::JsonSchema {
+        json_schema: JsonSchemaFormat {
+            name,
+            description: None,
+            schema: Some(schema),
+            strict: Some(true),
+        },
+    }
+}
+
+/// Adapts a schema to strict mode, which requires every property of every object
+/// to be required and disallows additional properties. Optional fields remain
+/// nullable.
+///
+/// Only schema positions are visited, so a property named e.g. `properties` or
+/// `items` is not mistaken for a schema keyword.
+fn make_strict(schema: &mut Value) {
+    let Value::Object(object) = schema else {
+        return;
+    };
+
+    if let Some(Value::Object(properties)) = object.get_mut("properties") {
+        properties.values_mut().for_each(make_strict);
+        let required = properties.keys().cloned().map(Value::String).collect();
+        object.insert("required".to_string(), Value::Array(required));
+        object.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+
+    match object.get_mut("items") {
+        Some(Value::Array(items)) => items.iter_mut().for_each(make_strict),
+        Some(items) => make_strict(items),
+        None => {}
+    }
+    for keyword in ["anyOf", "allOf", "oneOf"] {
+        if let Some(Value::Array(schemas)) = object.get_mut(keyword) {
+            schemas.iter_mut().for_each(make_strict);
+        }
+    }
+    for keyword in ["$defs", "definitions"] {
+        if let Some(Value::Object(definitions)) = object.get_mut(keyword) {
+            definitions.values_mut().for_each(make_strict);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ErrorCategory, Message};
+    use crate::models::{Model, Role};
+    use crate::test_utils::chat_response_with_choices;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, JsonSchema, PartialEq)]
+    struct Player {
+        name: String,
+        country: String,
+        caps: Option<u32>,
+    }
+
+    #[test]
+    fn test_response_format_is_strict() {
+        let ChatResponseFormat::JsonSchema { json_schema } = response_format_for::<Player>() else {
+            panic!("Expected a JSON schema response format");
+        };
+        let schema = json_schema.schema.unwrap();
+
+        assert_eq!(json_schema.name, "Player");
+        assert_eq!(json_schema.strict, Some(true));
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(schema["type"], "object");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["caps", "country", "name"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct Team {
+        properties: TeamProperties,
+        players: Vec<Player>,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct TeamProperties {
+        country: String,
+    }
+
+    #[test]
+    fn test_make_strict_only_visits_schemas() {
+        let ChatResponseFormat::JsonSchema { json_schema } = response_format_for::<Team>() else {
+            panic!("Expected a JSON schema response format");
+        };
+        let schema = json_schema.schema.unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+
+        let mut names: Vec<&String> = properties.keys().collect();
+        names.sort();
+        assert_eq!(names, ["players", "properties"]);
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["players", "properties"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+
+        let team_properties = &properties["properties"];
+        assert_eq!(team_properties["required"], serde_json::json!(["country"]));
+        assert_eq!(team_properties["additionalProperties"], false);
+
+        let player = &properties["players"]["items"];
+        assert_eq!(
+            player["required"],
+            serde_json::json!(["caps", "country", "name"])
+        );
+        assert_eq!(player["additionalProperties"], false);
+    }
+
+    #[tokio::test]
+    async fn test_chat_parsed() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {"name": "Player", "strict": true}
+                }
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chat_response_with_choices(
+                    serde_json::json!([{
+                        "index": 0,
+                        "message": {
+                            "role": "assistant",
+                            "content": "{\"name\":\"Tess\",\"country\":\"NL\",\"caps\":null}"
+                        },
+                        "finish_reason": "stop"
+                    }]),
+                )),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let input = ChatInput {
+            model: Model::Gpt_4o,
+            messages: vec![Message::new(Role::User, "Name a field hockey player")],
+            ..Default::default()
+        };
+        let player: Player = client.chat_parsed(input).await.unwrap();
+
+        assert_eq!(
+            player,
+            Player {
+                name: "Tess".to_string(),
+                country: "NL".to_string(),
+                caps: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_parsed_schema_mismatch() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chat_response_with_choices(
+                    serde_json::json!([{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "{\"name\":\"Tess\""},
+                        "finish_reason": "length"
+                    }]),
+                )),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ChatGPTClient::new("dummy_api_key", &server.uri());
+        let input = ChatInput {
+            model: Model::Gpt_4o,
+            messages: vec![Message::new(Role::User, "Name a field hockey player")],
+            ..Default::default()
+        };
+        let err = client.chat_parsed::<Player>(input).await.unwrap_err();
+
+        assert_eq!(err.category(), ErrorCategory::Deserialization);
+        match err {
+            ChatGPTError::UnexpectedResponse(message) => {
+                assert!(message.contains("{\"name\":\"Tess\""))
+            }
+            other => panic!("Expected UnexpectedResponse, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::client::ChatGPTError;
+use std::collections::HashMap;
+
+/// Determines what happens to a placeholder without a matching variable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MissingVariable {
+    /// Return a ChatGPTError::ConfigError.
+    Error,
+    /// Leave the placeholder in the output unchanged.
+    Leave,
+}
+
+/// Renders a template by substituting `{{name}}` placeholders with the given variables.
+///
+/// Whitespace around the name is ignored, so `{{ name }}` works as well.
+///
+/// # Arguments
+///
+/// * `template` - The template text containing `{{name}}` placeholders.
+/// * `vars` - The values to substitute, keyed by placeholder name.
+///
+/// # Errors
+///
+/// Returns a ChatGPTError::ConfigError if a placeholder has no matching variable.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::templates::render;
+/// use std::collections::HashMap;
+///
+/// let vars = HashMap::from([("name", "Ada".to_string())]);
+/// assert_eq!(render("Hello, {{name}}!", &vars).unwrap(), "Hello, Ada!");
+/// ```
+pub fn render(template: &str, vars: &HashMap<&str, String>) -> Result<String, ChatGPTError> {
+    render_with(template, vars, MissingVariable::Error)
+}
+
+/// Renders a template like [`render`], with the given handling of missing variables.
+///
+/// # Errors
+///
+/// Returns a ChatGPTError::ConfigError if a placeholder has no matching variable and
+/// `missing` is [`MissingVariable::Error`].
+pub fn render_with(
+    template: &str,
+    vars: &HashMap<&str, String>,
+    missing: MissingVariable,
+) -> Result<String, ChatGPTError> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + len + 2];
+        let name = placeholder[2..placeholder.len() - 2].trim();
+
+        output.push_str(&rest[..start]);
+        match vars.get(name) {
+            Some(value) => output.push_str(value),
+            None if missing == MissingVariable::Leave => output.push_str(placeholder),
+            None => {
+                return Err(ChatGPTError::ConfigError(format!(
+                    "Unresolved template variable '{name}'"
+                )))
+            }
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let vars = HashMap::from([
+            ("name", "Ada".to_string()),
+            ("topic", "field hockey".to_string()),
+        ]);
+
+        let rendered = render("Hi {{name}}, let's talk about {{ topic }}.", &vars).unwrap();
+        assert_eq!(rendered, "Hi Ada, let's talk about field hockey.");
+    }
+
+    #[test]
+    fn test_render_missing_variable_errors() {
+        let vars = HashMap::from([("name", "Ada".to_string())]);
+
+        let result = render("Hi {{name}}, you are {{age}}.", &vars);
+        assert!(matches!(result, Err(ChatGPTError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_render_missing_variable_left() {
+        let vars = HashMap::from([("name", "Ada".to_string())]);
+
+        let rendered = render_with(
+            "Hi {{name}}, you are {{age}}.",
+            &vars,
+            MissingVariable::Leave,
+        )
+        .unwrap();
+        assert_eq!(rendered, "Hi Ada, you are {{age}}.");
+    }
+
+    #[test]
+    fn test_render_unclosed_placeholder() {
+        let vars = HashMap::new();
+
+        let rendered = render("No {{placeholder here", &vars).unwrap();
+        assert_eq!(rendered, "No {{placeholder here");
+    }
+}
@@ -0,0 +1,54 @@
+//! Fixtures shared by the unit tests.
+
+use crate::client::ChatResponse;
+use serde_json::Value;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Returns a minimal chat completion response body with the reply "Hello there!".
+pub(crate) fn chat_response_json() -> Value {
+    serde_json::json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion",
+        "created": 1677652288,
+        "model": "gpt-4o",
+        "usage": {"prompt_tokens": 9, "completion_tokens": 12, "total_tokens": 21},
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "Hello there!"},
+            "finish_reason": "stop"
+        }]
+    })
+}
+
+/// Returns the response body of [`chat_response_json`] with the given choices.
+pub(crate) fn chat_response_with_choices(choices: Value) -> Value {
+    let mut json = chat_response_json();
+    json["choices"] = choices;
+    json
+}
+
+/// Returns the response of [`chat_response_json`] with the given assistant reply.
+pub(crate) fn chat_response_with_reply(content: &str) -> ChatResponse {
+    let mut json = chat_response_json();
+    json["choices"][0]["message"]["content"] = Value::from(content);
+    serde_json::from_value(json).unwrap()
+}
+
+/// Returns the response of [`chat_response_json`].
+pub(crate) fn chat_response() -> ChatResponse {
+    serde_json::from_value(chat_response_json()).unwrap()
+}
+
+/// Returns a successful API response with the body of [`chat_response_json`].
+pub(crate) fn chat_success() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(chat_response_json())
+}
+
+/// Answers every POST request to the server with [`chat_success`].
+pub(crate) async fn mount_chat_success(server: &MockServer) {
+    Mock::given(method("POST"))
+        .respond_with(chat_success())
+        .mount(server)
+        .await;
+}
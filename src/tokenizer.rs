@@ -1,3 +1,18 @@
+use crate::client::Message;
+#[cfg(feature = "tokenizer")]
+use crate::models::Model;
+use crate::models::Role;
+#[cfg(feature = "tokenizer")]
+use tiktoken_rs::{tokenizer::Tokenizer, CoreBPE};
+
+/// The number of tokens added for every message, for the `<|start|>{role}\n...<|end|>\n` markers.
+#[cfg(feature = "tokenizer")]
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// The number of tokens added to prime the reply with `<|start|>assistant<|message|>`.
+#[cfg(feature = "tokenizer")]
+const TOKENS_PER_REPLY: usize = 3;
+
 /// Counts the approximate number of tokens in a string.
 ///
 /// This function provides a rough estimate based on the assumption that
@@ -17,6 +32,102 @@ pub fn count_tokens(text: &str) -> usize {
     char_count / 4
 }
 
+/// Counts the exact number of tokens in a string, using the tiktoken encoding of the model.
+///
+/// Only available with the `tokenizer` feature.
+///
+/// # Arguments
+///
+/// * `model` - The model whose encoding is used, `o200k_base` for the GPT-4o models
+///   and `cl100k_base` for the other models.
+/// * `text` - A string slice that holds the text to be tokenized.
+///
+/// # Returns
+///
+/// * An usize representing the number of tokens in `text`.
+#[cfg(feature = "tokenizer")]
+pub fn count_tokens_for_model(model: &Model, text: &str) -> usize {
+    encoding_for_model(model)
+        .encode_with_special_tokens(text)
+        .len()
+}
+
+/// Counts the number of prompt tokens used by a list of messages.
+///
+/// Besides the content, every message uses a few tokens for its role and the
+/// markers around it, and a few tokens are used to prime the reply, as described
+/// in the OpenAI cookbook. Tool calls and images are not counted.
+///
+/// Only available with the `tokenizer` feature.
+///
+/// # Arguments
+///
+/// * `model` - The model whose encoding is used.
+/// * `messages` - The messages of the request.
+///
+/// # Returns
+///
+/// * An usize representing the number of prompt tokens of `messages`.
+#[cfg(feature = "tokenizer")]
+pub fn count_message_tokens(model: &Model, messages: &[Message]) -> usize {
+    let encoding = encoding_for_model(model);
+    let count = |text: &str| encoding.encode_with_special_tokens(text).len();
+
+    let message_tokens: usize = messages
+        .iter()
+        .map(|m| TOKENS_PER_MESSAGE + count(role_name(&m.role)) + count(&m.content.to_string()))
+        .sum();
+    message_tokens + TOKENS_PER_REPLY
+}
+
+/// Returns the role as it is sent to the API.
+#[cfg(feature = "tokenizer")]
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// Returns the tiktoken encoding used by the model.
+#[cfg(feature = "tokenizer")]
+fn encoding_for_model(model: &Model) -> &'static CoreBPE {
+    match tiktoken_rs::tokenizer::get_tokenizer(&model.to_string()) {
+        Some(Tokenizer::O200kBase) => tiktoken_rs::o200k_base_singleton(),
+        _ => tiktoken_rs::cl100k_base_singleton(),
+    }
+}
+
+/// Drops the oldest non-system messages until the estimated token count fits.
+///
+/// System messages are never removed, so the result may still exceed
+/// `max_tokens` when the system messages alone are too long. Token counts are
+/// estimated with [`count_tokens`].
+///
+/// # Arguments
+///
+/// * `messages` - The messages to truncate, oldest first.
+/// * `max_tokens` - The maximum number of estimated tokens to keep.
+///
+/// # Returns
+///
+/// * An usize representing the estimated number of tokens in the remaining messages.
+pub fn truncate_messages(messages: &mut Vec<Message>, max_tokens: usize) -> usize {
+    let mut total: usize = messages
+        .iter()
+        .map(|m| count_tokens(&m.content.to_string()))
+        .sum();
+    while total > max_tokens {
+        match messages.iter().position(|m| m.role != Role::System) {
+            Some(index) => total -= count_tokens(&messages.remove(index).content.to_string()),
+            None => break,
+        }
+    }
+    total
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,4 +141,60 @@ mod tests {
         );
         assert_eq!(count_tokens(""), 0);
     }
+
+    #[test]
+    fn test_truncate_messages_drops_oldest_non_system() {
+        let mut messages = vec![
+            Message::new(Role::System, "12345678"),
+            Message::new(Role::User, "12345678"),
+            Message::new(Role::Assistant, "12345678"),
+            Message::new(Role::User, "12345678"),
+        ];
+
+        let total = truncate_messages(&mut messages, 5);
+
+        assert_eq!(total, 4);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::System);
+        assert_eq!(messages[1].role, Role::User);
+    }
+
+    #[test]
+    fn test_truncate_messages_keeps_system_messages() {
+        let mut messages = vec![
+            Message::new(Role::System, "1234567812345678"),
+            Message::new(Role::User, "12345678"),
+        ];
+
+        let total = truncate_messages(&mut messages, 1);
+
+        assert_eq!(total, 4);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, Role::System);
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_count_tokens_for_model() {
+        assert_eq!(count_tokens_for_model(&Model::Gpt_4, "Hello, world!"), 4);
+        assert_eq!(count_tokens_for_model(&Model::Gpt_4o, "Hello, world!"), 4);
+        assert_eq!(
+            count_tokens_for_model(&Model::Gpt3_5Turbo, "hello world"),
+            2
+        );
+        assert_eq!(count_tokens_for_model(&Model::Gpt_4o, ""), 0);
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_count_message_tokens() {
+        let messages = vec![
+            Message::new(Role::System, "You are a helpful assistant."),
+            Message::new(Role::User, "Hello!"),
+        ];
+
+        // 3 per message + role + content, plus 3 to prime the reply
+        assert_eq!(count_message_tokens(&Model::Gpt_4, &messages), 19);
+        assert_eq!(count_message_tokens(&Model::Gpt_4, &[]), 3);
+    }
 }
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Represents the type of a tool. Currently only functions are supported.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolType {
+    Function,
+}
+
+/// Represents a tool the model may call.
+///
+/// # Examples
+///
+/// ```
+/// use chat_gpt_lib_rs::tools::Tool;
+/// use serde_json::json;
+///
+/// let tool = Tool::function(
+///     "get_weather",
+///     "Get the current weather for a city",
+///     json!({
+///         "type": "object",
+///         "properties": {"city": {"type": "string"}},
+///         "required": ["city"]
+///     }),
+/// );
+/// ```
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+    pub function: FunctionDefinition,
+}
+
+impl Tool {
+    /// Creates a function tool with the given name, description and JSON Schema parameters.
+    pub fn function(name: &str, description: &str, parameters: Value) -> Self {
+        Self {
+            tool_type: ToolType::Function,
+            function: FunctionDefinition {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                parameters: Some(parameters),
+            },
+        }
+    }
+}
+
+/// Represents the definition of a function the model may call.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The parameters the function accepts, described as a JSON Schema object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+/// Controls which tool, if any, the model calls.
+///
+/// The `ToolChoice` enum has the following variants:
+/// - `None`: The model does not call a tool and generates a message instead.
+/// - `Auto`: The model chooses between generating a message or calling tools.
+/// - `Required`: The model must call one or more tools.
+/// - `Function`: The model must call the function with the given name.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(try_from = "ToolChoiceRepr", into = "ToolChoiceRepr")]
+pub enum ToolChoice {
+    None,
+    Auto,
+    Required,
+    Function(String),
+}
+
+/// The wire format of a ToolChoice, either a mode string or a function object.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum ToolChoiceRepr {
+    Mode(String),
+    Function {
+        #[serde(rename = "type")]
+        tool_type: ToolType,
+        function: ToolChoiceFunction,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+struct ToolChoiceFunction {
+    name: String,
+}
+
+impl TryFrom<ToolChoiceRepr> for ToolChoice {
+    type Error = String;
+
+    fn try_from(repr: ToolChoiceRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ToolChoiceRepr::Mode(mode) => match mode.as_str() {
+                "none" => Ok(ToolChoice::None),
+                "auto" => Ok(ToolChoice::Auto),
+                "required" => Ok(ToolChoice::Required),
+                other => Err(format!("Unsupported tool choice: {other}")),
+            },
+            ToolChoiceRepr::Function { function, .. } => Ok(ToolChoice::Function(function.name)),
+        }
+    }
+}
+
+impl From<ToolChoice> for ToolChoiceRepr {
+    fn from(choice: ToolChoice) -> Self {
+        match choice {
+            ToolChoice::None => ToolChoiceRepr::Mode("none".to_string()),
+            ToolChoice::Auto => ToolChoiceRepr::Mode("auto".to_string()),
+            ToolChoice::Required => ToolChoiceRepr::Mode("required".to_string()),
+            ToolChoice::Function(name) => ToolChoiceRepr::Function {
+                tool_type: ToolType::Function,
+                function: ToolChoiceFunction { name },
+            },
+        }
+    }
+}
+
+/// Represents a call of a tool by the model.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub tool_type: ToolType,
+    pub function: FunctionCall,
+}
+
+/// Represents the function and arguments the model wants to call.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct FunctionCall {
+    pub name: String,
+    /// The arguments as a JSON string, which may not be valid JSON.
+    pub arguments: String,
+}
+
+impl FunctionCall {
+    /// Parses the arguments into the given type.
+    ///
+    /// # Errors
+    ///
+    /// Returns a serde_json::Error if the arguments are not valid JSON for `T`.
+    pub fn parse_arguments<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_serialization() {
+        let tool = Tool::function(
+            "get_weather",
+            "Get the weather",
+            json!({"type": "object", "properties": {}}),
+        );
+
+        assert_eq!(
+            serde_json::to_value(tool).unwrap(),
+            json!({
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Get the weather",
+                    "parameters": {"type": "object", "properties": {}}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_tool_choice_serde() {
+        let cases = [
+            (ToolChoice::None, json!("none")),
+            (ToolChoice::Auto, json!("auto")),
+            (ToolChoice::Required, json!("required")),
+            (
+                ToolChoice::Function("get_weather".to_string()),
+                json!({"type": "function", "function": {"name": "get_weather"}}),
+            ),
+        ];
+
+        for (choice, expected) in cases {
+            assert_eq!(serde_json::to_value(choice.clone()).unwrap(), expected);
+            assert_eq!(
+                serde_json::from_value::<ToolChoice>(expected).unwrap(),
+                choice
+            );
+        }
+
+        assert!(serde_json::from_value::<ToolChoice>(json!("sometimes")).is_err());
+    }
+
+    #[test]
+    fn test_function_call_parse_arguments() {
+        let call = FunctionCall {
+            name: "get_weather".to_string(),
+            arguments: r#"{"city": "Amsterdam"}"#.to_string(),
+        };
+
+        let arguments: Value = call.parse_arguments().unwrap();
+        assert_eq!(arguments["city"], "Amsterdam");
+    }
+}